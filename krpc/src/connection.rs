@@ -124,6 +124,7 @@ impl Connection {
             rpc.service,
             rpc.method,
             rpc.required_feature_flags,
+            rpc.request_id.as_ref(),
             &*rpc.request,
             Some(rpc.deadline - now),
         );
@@ -74,6 +74,7 @@ impl Proxy {
             required_feature_flags: call.required_feature_flags,
             timestamp: Instant::now(),
             deadline: call.deadline,
+            request_id: call.request_id,
             request: call.request,
             completer,
         };
@@ -13,6 +13,7 @@ use pb::rpc::{ErrorStatusPb, RemoteMethodPb, RequestHeader, ResponseHeader};
 use Error;
 use Options;
 use RequestBody;
+use RequestId;
 use RpcError;
 use RpcErrorCode;
 
@@ -81,12 +82,14 @@ impl Transport {
     ///
     /// If a fatal error is returned the transport is shut down. If a non-fatal error is returned,
     /// the RPC should be failed.
+    #[cfg_attr(feature = "cargo-clippy", allow(too_many_arguments))]
     pub fn send(
         &mut self,
         call_id: i32,
         service: &str,
         method: &str,
         required_feature_flags: &[u32],
+        request_id: Option<&RequestId>,
         body: &RequestBody,
         timeout: Option<Duration>,
     ) -> Result<(), Error> {
@@ -109,6 +112,7 @@ impl Transport {
             self.request_header
                 .required_feature_flags
                 .extend_from_slice(required_feature_flags);
+            self.request_header.request_id = request_id.cloned();
 
             let header_len = Message::encoded_len(&self.request_header);
             let body_len = body.encoded_len();
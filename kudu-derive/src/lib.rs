@@ -0,0 +1,123 @@
+//! Derives `kudu::TypedRow` for structs with named fields.
+//!
+//! Each field maps to the column with the same name, unless the column is named with a
+//! `#[kudu(column = "...")]` attribute. Field types must implement `kudu::Value`, and nullable
+//! columns may be mapped to `Option` fields.
+//!
+//! ```ignore
+//! #[derive(TypedRow)]
+//! struct Metric {
+//!     host: String,
+//!     #[kudu(column = "ts")]
+//!     timestamp: SystemTime,
+//!     value: Option<f64>,
+//! }
+//! ```
+
+extern crate proc_macro;
+extern crate proc_macro2;
+#[macro_use]
+extern crate quote;
+extern crate syn;
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use syn::{Data, DeriveInput, Fields, Lit, Meta, NestedMeta};
+
+#[proc_macro_derive(TypedRow, attributes(kudu))]
+pub fn derive_typed_row(input: TokenStream) -> TokenStream {
+    let input = syn::parse::<DeriveInput>(input).unwrap();
+    match typed_row(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+fn typed_row(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let fields = match input.data {
+        Data::Struct(ref data) => match data.fields {
+            Fields::Named(ref fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new(
+                    Span::call_site(),
+                    "TypedRow can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                "TypedRow can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut checks = Vec::new();
+    let mut writes = Vec::new();
+    let mut reads = Vec::new();
+    for (idx, field) in fields.iter().enumerate() {
+        let ident = field.ident.as_ref().unwrap();
+        let ty = &field.ty;
+        let column = match column_name(field)? {
+            Some(column) => column,
+            None => ident.to_string(),
+        };
+        checks.push(quote! { ::kudu::check_column::<#ty>(schema, #column)? });
+        writes.push(quote! { row.set_unchecked(column_indices[#idx], self.#ident); });
+        reads.push(quote! { #ident: row.get::<_, #ty>(column_indices[#idx])? });
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::kudu::TypedRow for #name #ty_generics #where_clause {
+            fn column_indices(schema: &::kudu::Schema) -> ::kudu::Result<Vec<usize>> {
+                Ok(vec![#(#checks),*])
+            }
+
+            unsafe fn write_row(
+                self,
+                column_indices: &[usize],
+                row: &mut ::kudu::Row<'static>,
+            ) {
+                #(#writes)*
+            }
+
+            fn read_row(column_indices: &[usize], row: &::kudu::Row) -> ::kudu::Result<Self> {
+                Ok(#name { #(#reads),* })
+            }
+        }
+    })
+}
+
+/// Returns the column name set with a `#[kudu(column = "...")]` attribute, if any.
+fn column_name(field: &syn::Field) -> syn::Result<Option<String>> {
+    let mut column = None;
+    for attr in &field.attrs {
+        if attr.path.segments.len() != 1 || attr.path.segments[0].ident != "kudu" {
+            continue;
+        }
+        let list = match attr.parse_meta()? {
+            Meta::List(list) => list,
+            meta => return Err(syn::Error::new_spanned(meta, "expected #[kudu(...)]")),
+        };
+        for nested in list.nested {
+            match nested {
+                NestedMeta::Meta(Meta::NameValue(ref name_value))
+                    if name_value.ident == "column" =>
+                {
+                    match name_value.lit {
+                        Lit::Str(ref name) => column = Some(name.value()),
+                        ref lit => {
+                            return Err(syn::Error::new_spanned(lit, "expected a column name"))
+                        }
+                    }
+                }
+                nested => {
+                    return Err(syn::Error::new_spanned(nested, "unknown kudu attribute"));
+                }
+            }
+        }
+    }
+    Ok(column)
+}
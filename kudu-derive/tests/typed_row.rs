@@ -0,0 +1,82 @@
+extern crate kudu;
+#[macro_use]
+extern crate kudu_derive;
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use kudu::{Column, DataType, RowMapper, SchemaBuilder};
+
+#[derive(Debug, PartialEq, TypedRow)]
+struct Metric {
+    host: String,
+    #[kudu(column = "ts")]
+    timestamp: SystemTime,
+    value: Option<f64>,
+}
+
+fn schema() -> kudu::Schema {
+    SchemaBuilder::new()
+        .add_column(Column::new("host", DataType::String).set_not_null())
+        .add_column(Column::new("ts", DataType::Timestamp).set_not_null())
+        .add_column(Column::new("region", DataType::String).set_nullable())
+        .add_column(Column::new("value", DataType::Double).set_nullable())
+        .set_primary_key(vec!["host", "ts"])
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn round_trip() {
+    let mapper = RowMapper::<Metric>::new(&schema()).unwrap();
+
+    let metric = Metric {
+        host: "host-1".to_string(),
+        timestamp: UNIX_EPOCH + Duration::from_secs(1_000_000),
+        value: Some(0.5),
+    };
+    let row = mapper.to_row(Metric {
+        host: metric.host.clone(),
+        timestamp: metric.timestamp,
+        value: metric.value,
+    });
+    assert_eq!("host-1", row.get::<_, &str>("host").unwrap());
+    assert!(!row.is_set("region").unwrap());
+    assert_eq!(metric, mapper.from_row(&row).unwrap());
+
+    let row = mapper.to_row(Metric {
+        value: None,
+        ..metric
+    });
+    assert!(row.is_null("value").unwrap());
+    assert_eq!(None, mapper.from_row(&row).unwrap().value);
+}
+
+#[test]
+fn schema_mismatch() {
+    #[derive(TypedRow)]
+    struct WrongType {
+        host: i32,
+    }
+
+    #[derive(TypedRow)]
+    struct NotNull {
+        region: String,
+    }
+
+    #[derive(TypedRow)]
+    struct Nullable {
+        host: Option<String>,
+    }
+
+    #[derive(TypedRow)]
+    struct Missing {
+        missing: String,
+    }
+
+    assert!(RowMapper::<WrongType>::new(&schema()).is_err());
+    assert!(RowMapper::<Nullable>::new(&schema()).is_err());
+    assert!(RowMapper::<Missing>::new(&schema()).is_err());
+
+    // Non-null fields may be mapped to nullable columns.
+    assert!(RowMapper::<NotNull>::new(&schema()).is_ok());
+}
@@ -1,11 +1,11 @@
 use std::fmt;
 use std::str;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::Instant;
 
 use futures::future::{self, Loop};
 use futures::Future;
-use parking_lot::Mutex;
 use tokio_timer::Delay;
 
 use pb::master::{
@@ -16,6 +16,7 @@ use pb::ExpectField;
 
 use backoff::Backoff;
 use meta_cache::MetaCache;
+use request_tracker::RequestTracker;
 use table::AlterTableBuilder;
 use table::Table;
 use table::TableBuilder;
@@ -33,7 +34,8 @@ use TabletServerInfo;
 #[derive(Clone)]
 pub struct Client {
     meta_cache: MetaCache,
-    latest_observed_timestamp: Arc<Mutex<u64>>, // Replace with AtomicU64 when stable.
+    latest_observed_timestamp: Arc<AtomicU64>,
+    request_tracker: RequestTracker,
 }
 
 impl Client {
@@ -49,7 +51,8 @@ impl Client {
             .and_then(|master_addresses| MetaCache::new(master_addresses, options))
             .map(move |meta_cache| Client {
                 meta_cache,
-                latest_observed_timestamp: Arc::new(Mutex::new(0)),
+                latest_observed_timestamp: Arc::new(AtomicU64::new(0)),
+                request_tracker: RequestTracker::new(),
             })
     }
 
@@ -299,26 +302,49 @@ impl Client {
     where
         S: Into<String>,
     {
-        self.meta_cache
-            .open_table(TableIdentifierPb::from(table.into()), self.deadline())
+        self.meta_cache.open_table(
+            TableIdentifierPb::from(table.into()),
+            self.clone(),
+            self.deadline(),
+        )
     }
 
     /// Returns an open table.
     pub fn open_table_by_id(&mut self, id: TableId) -> impl Future<Item = Table, Error = Error> {
-        self.meta_cache.open_table(id.into(), self.deadline())
+        self.meta_cache
+            .open_table(id.into(), self.clone(), self.deadline())
     }
 
+    /// Returns the latest hybrid timestamp observed by the client, or 0 if no timestamp has been
+    /// observed.
+    ///
+    /// The timestamp is propagated to tablet servers on writes and scans, which guarantees that
+    /// subsequent operations are causally ordered after everything the client has observed.
     pub fn latest_observed_timestamp(&self) -> u64 {
-        *self.latest_observed_timestamp.lock()
+        self.latest_observed_timestamp.load(Ordering::Acquire)
     }
 
+    /// Records a hybrid timestamp observed outside of the client, for instance from another
+    /// client. Timestamps older than the latest observed timestamp are ignored.
     pub fn observe_timestamp(&self, timestamp: u64) {
-        let mut latest = self.latest_observed_timestamp.lock();
-        if timestamp > *latest {
-            *latest = timestamp;
+        let mut latest = self.latest_observed_timestamp.load(Ordering::Acquire);
+        while timestamp > latest {
+            match self.latest_observed_timestamp.compare_exchange_weak(
+                latest,
+                timestamp,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => break,
+                Err(current) => latest = current,
+            }
         }
     }
 
+    pub(crate) fn request_tracker(&self) -> &RequestTracker {
+        &self.request_tracker
+    }
+
     fn deadline(&self) -> Instant {
         Instant::now() + self.meta_cache.options().admin_timeout
     }
@@ -336,9 +362,12 @@ mod tests {
     use super::*;
     use mini_cluster::{MiniCluster, MiniClusterConfig};
     use schema::tests::simple_schema;
+    use AlterColumnBuilder;
     use AlterTableBuilder;
     use Column;
+    use CompressionType;
     use DataType;
+    use EncodingType;
     use RangePartitionBound;
     use SchemaBuilder;
     use TableBuilder;
@@ -438,6 +467,11 @@ mod tests {
 
         let mut alter_builder = AlterTableBuilder::new();
         alter_builder.add_column(Column::new("c0", DataType::Int32));
+        alter_builder.add_column(
+            Column::new("c1", DataType::Int64)
+                .set_not_null()
+                .set_default_value(42i64),
+        );
 
         let _ = runtime
             .block_on(client.alter_table("t", alter_builder))
@@ -448,7 +482,59 @@ mod tests {
             .expect("open_table")
             .schema()
             .clone();
-        assert_eq!(3, schema.columns().len());
+        assert_eq!(4, schema.columns().len());
+        assert_eq!(Some(42), schema.columns()[3].default_value::<i64>().unwrap());
+
+        let mut alter_builder = AlterTableBuilder::new();
+        alter_builder.alter_column(
+            AlterColumnBuilder::new(&schema.columns()[3])
+                .set_encoding(EncodingType::BitShuffle)
+                .set_compression(CompressionType::Lz4)
+                .set_default_value(7i64),
+        );
+        runtime
+            .block_on(client.alter_table("t", alter_builder))
+            .expect("alter column");
+
+        let schema = runtime
+            .block_on(client.open_table("t"))
+            .expect("open_table")
+            .schema()
+            .clone();
+        assert_eq!(EncodingType::BitShuffle, schema.columns()[3].encoding());
+        assert_eq!(CompressionType::Lz4, schema.columns()[3].compression());
+        assert_eq!(Some(7), schema.columns()[3].default_value::<i64>().unwrap());
+
+        // Encodings and default values must be valid for the column type.
+        let mut alter_builder = AlterTableBuilder::new();
+        alter_builder.alter_column(
+            AlterColumnBuilder::new(&schema.columns()[3])
+                .set_encoding(EncodingType::Dictionary),
+        );
+        assert!(alter_builder.result.is_err());
+        let mut alter_builder = AlterTableBuilder::new();
+        alter_builder.alter_column(
+            AlterColumnBuilder::new(&schema.columns()[3])
+                .set_default_value("foo"),
+        );
+        assert!(alter_builder.result.is_err());
+        let decimal = Column::new("d", DataType::Decimal128);
+        let mut alter_builder = AlterTableBuilder::new();
+        alter_builder.alter_column(
+            AlterColumnBuilder::new(&decimal).set_encoding(EncodingType::BitShuffle),
+        );
+        assert!(alter_builder.result.is_ok());
+        let mut alter_builder = AlterTableBuilder::new();
+        alter_builder
+            .alter_column(AlterColumnBuilder::new(&decimal).set_encoding(EncodingType::RunLength));
+        assert!(alter_builder.result.is_err());
+
+        // Columns which are not nullable must have a default value.
+        let mut alter_builder = AlterTableBuilder::new();
+        alter_builder.add_column(Column::new("c2", DataType::Int32).set_not_null());
+        runtime
+            .block_on(client.alter_table("t", alter_builder))
+            .unwrap_err();
 
         let mut alter_builder = AlterTableBuilder::new();
         alter_builder.drop_range_partition(
@@ -472,7 +558,8 @@ mod tests {
                 &RangePartitionBound::Inclusive(lower_bound),
                 &RangePartitionBound::Exclusive(upper_bound),
             ).rename_table("u")
-            .drop_column("c0");
+            .drop_column("c0")
+            .drop_column("c1");
         runtime
             .block_on(client.alter_table_by_id(table_id, alter_builder))
             .unwrap();
@@ -1,11 +1,12 @@
 use std::cmp::Ordering;
 use std::ops::Bound;
-use std::{f32, f64, i16, i32, i64, i8};
+use std::{f32, f64, i128, i16, i32, i64, i8};
 
 use ieee754::Ieee754;
 
 use bounds::IntoBounds;
-use pb::ColumnPredicatePb;
+use pb::{ColumnPredicatePb, ExpectField};
+use util;
 use Column;
 use Error;
 use PhysicalType;
@@ -307,6 +308,97 @@ impl Filter {
         }
     }
 
+    pub(crate) fn from_pb(pb: ColumnPredicatePb, column: &Column) -> Result<Filter> {
+        use pb::column_predicate_pb::*;
+        let physical_type = column.data_type().physical_type();
+        let filter = match pb.predicate.expect_field("ColumnPredicatePB", "predicate")? {
+            Predicate::IsNull(_) => Filter::IsNull,
+            Predicate::IsNotNull(_) => Filter::IsNotNull,
+            Predicate::Equality(Equality { value }) => Filter::Equals {
+                physical_type,
+                value: value.expect_field("ColumnPredicatePB.Equality", "value")?,
+            },
+            Predicate::Range(Range { lower, upper }) => Filter::Range {
+                physical_type,
+                lower_bound: lower,
+                upper_bound: upper,
+            },
+            Predicate::InList(InList { values }) => Filter::In {
+                physical_type,
+                values,
+            },
+        };
+        Ok(filter.simplify())
+    }
+
+    /// Restricts the filter to the values of the column. Decimal columns hold a narrower range of
+    /// values than their physical type: the unscaled values within ±(10^precision - 1).
+    pub(crate) fn clamp(self, column: &Column) -> Filter {
+        let max = match column.precision() {
+            Some(precision) => util::decimal_max(precision),
+            None => return self,
+        };
+        let in_range = |physical_type, value: &Vec<u8>| {
+            let value = util::decode_decimal(physical_type, value);
+            -max <= value && value <= max
+        };
+
+        match self {
+            Filter::Equals { physical_type, value } => {
+                if in_range(physical_type, &value) {
+                    Filter::Equals { physical_type, value }
+                } else {
+                    Filter::None
+                }
+            }
+            Filter::Range {
+                physical_type,
+                lower_bound,
+                upper_bound,
+            } => {
+                let lower = lower_bound
+                    .as_ref()
+                    .map(|lower| util::decode_decimal(physical_type, lower));
+                let upper = upper_bound
+                    .as_ref()
+                    .map(|upper| util::decode_decimal(physical_type, upper));
+                if lower.map_or(false, |lower| lower > max)
+                    || upper.map_or(false, |upper| upper <= -max)
+                {
+                    return Filter::None;
+                }
+                let upper_bound = upper_bound.filter(|_| upper.unwrap() <= max);
+                if lower == Some(max) {
+                    // Only the maximum value is in range, unless the upper bound excludes it.
+                    return match upper_bound {
+                        Some(_) => Filter::None,
+                        None => Filter::Equals {
+                            physical_type,
+                            value: lower_bound.unwrap(),
+                        },
+                    };
+                }
+                let lower_bound = lower_bound.filter(|_| lower.unwrap() > -max);
+                Filter::Range {
+                    physical_type,
+                    lower_bound,
+                    upper_bound,
+                }.simplify()
+            }
+            Filter::In {
+                physical_type,
+                mut values,
+            } => {
+                values.retain(|value| in_range(physical_type, value));
+                Filter::In {
+                    physical_type,
+                    values,
+                }.simplify()
+            }
+            other => other,
+        }
+    }
+
     /// Checks that the filter is compatible with the provided column.
     pub(crate) fn check_type(&self, column: &Column) -> Result<()> {
         match *self {
@@ -349,6 +441,9 @@ impl TypeInfo {
         fn int64_cmp(a: &Vec<u8>, b: &Vec<u8>) -> Ordering {
             unsafe { i64::decode(a).cmp(&i64::decode(b)) }
         }
+        fn int128_cmp(a: &Vec<u8>, b: &Vec<u8>) -> Ordering {
+            unsafe { i128::decode(a).cmp(&i128::decode(b)) }
+        }
         fn float_cmp(a: &Vec<u8>, b: &Vec<u8>) -> Ordering {
             unsafe { f32::decode(a).partial_cmp(&f32::decode(b)).unwrap() }
         }
@@ -390,6 +485,13 @@ impl TypeInfo {
                 a != i64::MAX && a + 1 == b
             }
         }
+        fn int128_are_consecutive(a: &Vec<u8>, b: &Vec<u8>) -> bool {
+            unsafe {
+                let a = i128::decode(a);
+                let b = i128::decode(b);
+                a != i128::MAX && a + 1 == b
+            }
+        }
         #[cfg_attr(feature = "cargo-clippy", allow(float_cmp))]
         fn float_are_consecutive(a: &Vec<u8>, b: &Vec<u8>) -> bool {
             unsafe {
@@ -431,6 +533,9 @@ impl TypeInfo {
         fn int64_is_min(data: &Vec<u8>) -> bool {
             unsafe { i64::decode(data) == i64::MIN }
         }
+        fn int128_is_min(data: &Vec<u8>) -> bool {
+            unsafe { i128::decode(data) == i128::MIN }
+        }
         fn float_is_min(data: &Vec<u8>) -> bool {
             unsafe { f32::decode(data) == f32::NEG_INFINITY }
         }
@@ -456,6 +561,9 @@ impl TypeInfo {
         fn int64_is_max(data: &Vec<u8>) -> bool {
             unsafe { i64::decode(data) == i64::MAX }
         }
+        fn int128_is_max(data: &Vec<u8>) -> bool {
+            unsafe { i128::decode(data) == i128::MAX }
+        }
         fn float_is_max(data: &Vec<u8>) -> bool {
             unsafe { f32::decode(data) == f32::INFINITY }
         }
@@ -497,6 +605,12 @@ impl TypeInfo {
                 is_min: int64_is_min,
                 is_max: int64_is_max,
             },
+            PhysicalType::Int128 => TypeInfo {
+                cmp: int128_cmp,
+                are_consecutive: int128_are_consecutive,
+                is_min: int128_is_min,
+                is_max: int128_is_max,
+            },
             PhysicalType::Float => TypeInfo {
                 cmp: float_cmp,
                 are_consecutive: float_are_consecutive,
@@ -518,3 +632,29 @@ impl TypeInfo {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use DataType;
+
+    #[test]
+    fn test_clamp_decimal() {
+        let column = Column::new("d", DataType::Decimal32).set_precision(2);
+        assert_eq!(Filter::IsNotNull, Filter::range(-99i32..).clamp(&column));
+        assert_eq!(Filter::IsNotNull, Filter::range(-1000i32..1000).clamp(&column));
+        assert_eq!(Filter::range(..0i32), Filter::range(-500i32..0).clamp(&column));
+        assert_eq!(Filter::equals(99i32), Filter::range(99i32..).clamp(&column));
+        assert_eq!(Filter::None, Filter::range(100i32..).clamp(&column));
+        assert_eq!(Filter::None, Filter::range(..-99i32).clamp(&column));
+        assert_eq!(Filter::None, Filter::equals(100i32).clamp(&column));
+        assert_eq!(
+            Filter::equals(-99i32),
+            Filter::in_list(vec![-100i32, -99, 100]).clamp(&column)
+        );
+
+        let column = Column::new("i", DataType::Int32);
+        assert_eq!(Filter::range(100i32..), Filter::range(100i32..).clamp(&column));
+    }
+}
@@ -2,7 +2,7 @@
 
 //! Utility functions for working with keys.
 
-use std::{f32, f64, i16, i32, i64, i8};
+use std::{f32, f64, i128, i16, i32, i64, i8};
 
 use byteorder::{BigEndian, ByteOrder, NativeEndian, WriteBytesExt};
 use ieee754::Ieee754;
@@ -73,6 +73,12 @@ pub(crate) fn encode_primary_key(row: &Row) -> Result<Vec<u8>> {
     Ok(buf)
 }
 
+/// Encodes a subset of the columns of a row with the key encoding. Encoded keys compare in the
+/// same order as the column values they encode.
+pub(crate) fn encode_key(row: &Row, idxs: &[usize], buf: &mut Vec<u8>) -> Result<()> {
+    encode_columns(row, idxs.iter().cloned(), buf)
+}
+
 pub(crate) fn encode_partition_key(
     partition_schema: &PartitionSchema,
     row: &Row,
@@ -100,13 +106,18 @@ pub(crate) fn encode_hash_partition_key(
 ) -> Result<()> {
     let len = buf.len();
     encode_columns(row, hash_schema.columns().iter().cloned(), buf)?;
-    let bucket = murmur2_64(&buf[len..], u64::from(hash_schema.seed()))
-        % u64::from(hash_schema.num_buckets());
+    let bucket = hash_bucket(hash_schema, &buf[len..]);
     buf.truncate(len);
-    buf.write_u32::<BigEndian>(bucket as u32).unwrap();
+    buf.write_u32::<BigEndian>(bucket).unwrap();
     Ok(())
 }
 
+/// Returns the hash bucket of the encoded hash partition columns.
+pub(crate) fn hash_bucket(hash_schema: &HashPartitionSchema, encoded_columns: &[u8]) -> u32 {
+    (murmur2_64(encoded_columns, u64::from(hash_schema.seed()))
+        % u64::from(hash_schema.num_buckets())) as u32
+}
+
 fn encode_columns<I>(row: &Row, idxs: I, buf: &mut Vec<u8>) -> Result<()>
 where
     I: Iterator<Item = usize> + ExactSizeIterator,
@@ -126,12 +137,13 @@ fn encode_column(row: &Row, idx: usize, is_last: bool, buf: &mut Vec<u8>) -> Res
         DataType::Int16 => buf
             .write_i16::<BigEndian>(row.get::<_, i16>(idx)? ^ i16::MIN)
             .unwrap(),
-        DataType::Int32 => buf
+        DataType::Int32 | DataType::Decimal32 => buf
             .write_i32::<BigEndian>(row.get::<_, i32>(idx)? ^ i32::MIN)
             .unwrap(),
-        DataType::Int64 | DataType::Timestamp => buf
+        DataType::Int64 | DataType::Timestamp | DataType::Decimal64 => buf
             .write_i64::<BigEndian>(row.get::<_, i64>(idx)? ^ i64::MIN)
             .unwrap(),
+        DataType::Decimal128 => encode_i128(row.get(idx)?, buf),
         DataType::Binary | DataType::String => encode_binary(row.get(idx)?, is_last, buf),
         DataType::Bool | DataType::Float | DataType::Double => {
             panic!(
@@ -143,6 +155,38 @@ fn encode_column(row: &Row, idx: usize, is_last: bool, buf: &mut Vec<u8>) -> Res
     Ok(())
 }
 
+/// Encodes a value, as encoded by `Value::encode`, to the key encoding of the data type.
+///
+/// The value must have the physical type of the data type.
+pub(crate) fn encode_value(data_type: DataType, value: &[u8], is_last: bool, buf: &mut Vec<u8>) {
+    unsafe {
+        match data_type {
+            DataType::Int8 => buf.push((i8::decode(value) ^ i8::MIN) as u8),
+            DataType::Int16 => buf
+                .write_i16::<BigEndian>(i16::decode(value) ^ i16::MIN)
+                .unwrap(),
+            DataType::Int32 | DataType::Decimal32 => buf
+                .write_i32::<BigEndian>(i32::decode(value) ^ i32::MIN)
+                .unwrap(),
+            DataType::Int64 | DataType::Timestamp | DataType::Decimal64 => buf
+                .write_i64::<BigEndian>(i64::decode(value) ^ i64::MIN)
+                .unwrap(),
+            DataType::Decimal128 => encode_i128(i128::decode(value), buf),
+            DataType::Binary | DataType::String => encode_binary(value, is_last, buf),
+            DataType::Bool | DataType::Float | DataType::Double => {
+                panic!("illegal type {:?} in key", data_type);
+            }
+        }
+    }
+}
+
+/// Encodes a 128-bit integer with the key encoding, as big-endian with the sign bit flipped.
+fn encode_i128(value: i128, buf: &mut Vec<u8>) {
+    let value = (value ^ i128::MIN) as u128;
+    buf.write_u64::<BigEndian>((value >> 64) as u64).unwrap();
+    buf.write_u64::<BigEndian>(value as u64).unwrap();
+}
+
 fn encode_binary(value: &[u8], is_last: bool, buf: &mut Vec<u8>) {
     if is_last {
         buf.extend_from_slice(value);
@@ -210,14 +254,20 @@ fn decode_column<'a>(row: &mut Row, idx: usize, is_last: bool, key: &'a [u8]) ->
                 row.set_unchecked(idx, BigEndian::read_i16(key) ^ i16::MIN);
                 Ok(&key[2..])
             }
-            DataType::Int32 => {
+            DataType::Int32 | DataType::Decimal32 => {
                 row.set_unchecked(idx, BigEndian::read_i32(key) ^ i32::MIN);
                 Ok(&key[4..])
             }
-            DataType::Int64 | DataType::Timestamp => {
+            DataType::Int64 | DataType::Timestamp | DataType::Decimal64 => {
                 row.set_unchecked(idx, BigEndian::read_i64(key) ^ i64::MIN);
                 Ok(&key[8..])
             }
+            DataType::Decimal128 => {
+                let value = u128::from(BigEndian::read_u64(key)) << 64
+                    | u128::from(BigEndian::read_u64(&key[8..]));
+                row.set_unchecked(idx, value as i128 ^ i128::MIN);
+                Ok(&key[16..])
+            }
             DataType::Binary => {
                 let (remaining, value) = decode_binary(key, is_last)?;
                 row.set_unchecked(idx, value);
@@ -312,20 +362,27 @@ fn increment_cell(row: &mut Row, idx: usize) -> bool {
             }
             row.set(idx, val + 1).unwrap();
         }
-        DataType::Int32 => {
+        DataType::Int32 | DataType::Decimal32 => {
             let val: i32 = row.get(idx).unwrap();
             if val == i32::MAX {
                 return false;
             }
             row.set(idx, val + 1).unwrap();
         }
-        DataType::Int64 | DataType::Timestamp => {
+        DataType::Int64 | DataType::Timestamp | DataType::Decimal64 => {
             let val: i64 = row.get(idx).unwrap();
             if val == i64::MAX {
                 return false;
             }
             row.set(idx, val + 1).unwrap();
         }
+        DataType::Decimal128 => {
+            let val: i128 = row.get(idx).unwrap();
+            if val == i128::MAX {
+                return false;
+            }
+            row.set(idx, val + 1).unwrap();
+        }
         DataType::Binary | DataType::String => {
             let mut val: Vec<u8> = row.get(idx).unwrap();
             val.push(0u8);
@@ -372,8 +429,9 @@ fn is_cell_equal(a: &Row, b: &Row, idx: usize) -> bool {
         DataType::Bool => cmp::<bool>(a, b, idx),
         DataType::Int8 => cmp::<i8>(a, b, idx),
         DataType::Int16 => cmp::<i16>(a, b, idx),
-        DataType::Int32 => cmp::<i32>(a, b, idx),
-        DataType::Int64 | DataType::Timestamp => cmp::<i64>(a, b, idx),
+        DataType::Int32 | DataType::Decimal32 => cmp::<i32>(a, b, idx),
+        DataType::Int64 | DataType::Timestamp | DataType::Decimal64 => cmp::<i64>(a, b, idx),
+        DataType::Decimal128 => cmp::<i128>(a, b, idx),
         DataType::Binary | DataType::String => cmp::<&[u8]>(a, b, idx),
         // TODO: do bitwise cmp for floats?
         DataType::Float => cmp::<f32>(a, b, idx),
@@ -409,16 +467,21 @@ fn is_cell_incremented(lower: &Row, upper: &Row, idx: usize) -> bool {
             let upper: i16 = upper.get(idx).unwrap();
             lower < i16::MAX && lower + 1 == upper
         }
-        DataType::Int32 => {
+        DataType::Int32 | DataType::Decimal32 => {
             let lower: i32 = lower.get(idx).unwrap();
             let upper: i32 = upper.get(idx).unwrap();
             lower < i32::MAX && lower + 1 == upper
         }
-        DataType::Int64 | DataType::Timestamp => {
+        DataType::Int64 | DataType::Timestamp | DataType::Decimal64 => {
             let lower: i64 = lower.get(idx).unwrap();
             let upper: i64 = upper.get(idx).unwrap();
             lower < i64::MAX && lower + 1 == upper
         }
+        DataType::Decimal128 => {
+            let lower: i128 = lower.get(idx).unwrap();
+            let upper: i128 = upper.get(idx).unwrap();
+            lower < i128::MAX && lower + 1 == upper
+        }
         DataType::Binary | DataType::String => {
             let lower: &[u8] = lower.get(idx).unwrap();
             let upper: &[u8] = upper.get(idx).unwrap();
@@ -500,4 +563,34 @@ mod test {
             assert_eq!(row, decoded_row);
         }
     }
+
+    #[test]
+    fn decimal_primary_key_encode_decode() {
+        let schema = SchemaBuilder::new()
+            .add_column(Column::new("a", DataType::Decimal32).set_not_null())
+            .add_column(Column::new("b", DataType::Decimal64).set_not_null())
+            .add_column(Column::new("c", DataType::Decimal128).set_not_null())
+            .set_primary_key(vec!["a", "b", "c"])
+            .build()
+            .unwrap();
+
+        let mut keys = Vec::new();
+        for &value in &[-1_000_000_000i64, -1, 0, 1, 1_000_000_000] {
+            let mut row = schema.new_row();
+            row.set(0, value as i32 / 2).unwrap();
+            row.set(1, value).unwrap();
+            row.set(2, i128::from(value) << 64).unwrap();
+            let key = encode_primary_key(&row).unwrap();
+            assert_eq!(28, key.len());
+
+            let decoded_row = decode_primary_key(&schema, &key).unwrap();
+            assert_eq!(row, decoded_row);
+            keys.push(key);
+        }
+
+        // Encoded keys compare in the same order as the values.
+        let mut sorted_keys = keys.clone();
+        sorted_keys.sort();
+        assert_eq!(keys, sorted_keys);
+    }
 }
@@ -1,5 +1,7 @@
-#![feature(nll)]
+#![feature(integer_atomics, nll)]
 
+#[cfg(feature = "arrow")]
+extern crate arrow;
 extern crate byteorder;
 extern crate bytes;
 extern crate ieee754;
@@ -9,6 +11,9 @@ extern crate parking_lot;
 extern crate prost;
 extern crate prost_types;
 extern crate rand;
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde;
 extern crate tokio;
 extern crate tokio_timer;
 extern crate url;
@@ -25,6 +30,9 @@ extern crate lazy_static;
 extern crate log;
 #[macro_use]
 extern crate prost_derive;
+#[cfg(all(feature = "serde", test))]
+#[macro_use]
+extern crate serde_json;
 #[cfg(test)]
 extern crate tempdir;
 
@@ -42,16 +50,23 @@ mod key;
 mod meta_cache;
 mod operation;
 mod partition;
+mod partition_pruner;
 mod pb;
+#[cfg(feature = "arrow")]
+mod record_batch;
 mod replica;
+mod request_tracker;
 mod retry;
 mod row;
 mod scanner;
 mod schema;
+#[cfg(feature = "serde")]
+mod serialization;
 mod server;
 mod table;
 mod tablet;
 mod timestamp;
+mod typed_row;
 mod util;
 mod value;
 mod writer;
@@ -67,16 +82,19 @@ pub use error::*;
 pub use filter::*;
 pub use operation::*;
 pub use partition::*;
+#[cfg(feature = "arrow")]
+pub use record_batch::*;
+pub use replica::{Selection, Speculation};
 pub use row::Row;
 pub use scanner::*;
 pub use schema::*;
 pub use server::*;
 pub use table::*;
 pub use tablet::*;
+pub use typed_row::*;
+pub use value::Value;
 pub use writer::*;
 
-use value::Value;
-
 use std::fmt;
 use std::str;
 use std::time::Duration;
@@ -84,6 +102,7 @@ use std::time::Duration;
 pub use krpc::HostPort;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum DataType {
     Bool,
     Int8,
@@ -95,6 +114,9 @@ pub enum DataType {
     Double,
     Binary,
     String,
+    Decimal32,
+    Decimal64,
+    Decimal128,
 }
 
 impl DataType {
@@ -109,9 +131,55 @@ impl DataType {
         match self {
             DataType::Bool | DataType::Int8 => 1,
             DataType::Int16 => 2,
-            DataType::Int32 | DataType::Float => 4,
-            DataType::Int64 | DataType::Timestamp | DataType::Double => 8,
-            DataType::Binary | DataType::String => 16,
+            DataType::Int32 | DataType::Float | DataType::Decimal32 => 4,
+            DataType::Int64 | DataType::Timestamp | DataType::Double | DataType::Decimal64 => 8,
+            DataType::Binary | DataType::String | DataType::Decimal128 => 16,
+        }
+    }
+
+    /// Returns the minimum and maximum precision of a decimal data type, or `None` if the data
+    /// type is not a decimal type.
+    pub(crate) fn decimal_precision_range(self) -> Option<(u8, u8)> {
+        match self {
+            DataType::Decimal32 => Some((1, 9)),
+            DataType::Decimal64 => Some((10, 18)),
+            DataType::Decimal128 => Some((19, 38)),
+            _ => None,
+        }
+    }
+
+    /// Returns the encodings which are valid for columns of the data type.
+    pub(crate) fn encodings(self) -> &'static [EncodingType] {
+        match self {
+            DataType::Int8
+            | DataType::Int16
+            | DataType::Int32
+            | DataType::Int64
+            | DataType::Timestamp
+            | DataType::Decimal32
+            | DataType::Decimal64 => &[
+                EncodingType::Auto,
+                EncodingType::Plain,
+                EncodingType::BitShuffle,
+                EncodingType::RunLength,
+            ],
+            // Run-length encoding is not supported for 128 bit integers.
+            DataType::Decimal128 => &[
+                EncodingType::Auto,
+                EncodingType::Plain,
+                EncodingType::BitShuffle,
+            ],
+            DataType::Bool | DataType::Float | DataType::Double => &[
+                EncodingType::Auto,
+                EncodingType::Plain,
+                EncodingType::RunLength,
+            ],
+            DataType::String | DataType::Binary => &[
+                EncodingType::Auto,
+                EncodingType::Plain,
+                EncodingType::Dictionary,
+                EncodingType::RunLength,
+            ],
         }
     }
 
@@ -127,6 +195,9 @@ impl DataType {
             DataType::Double => pb::DataType::Double,
             DataType::Binary => pb::DataType::Binary,
             DataType::String => pb::DataType::String,
+            DataType::Decimal32 => pb::DataType::Decimal32,
+            DataType::Decimal64 => pb::DataType::Decimal64,
+            DataType::Decimal128 => pb::DataType::Decimal128,
         };
         val as i32
     }
@@ -143,6 +214,9 @@ impl DataType {
             pb::DataType::Double => Ok(DataType::Double),
             pb::DataType::Binary => Ok(DataType::Binary),
             pb::DataType::String => Ok(DataType::String),
+            pb::DataType::Decimal32 => Ok(DataType::Decimal32),
+            pb::DataType::Decimal64 => Ok(DataType::Decimal64),
+            pb::DataType::Decimal128 => Ok(DataType::Decimal128),
             _ => Err(Error::Serialization("unknown data type".to_string())),
         }
     }
@@ -152,8 +226,9 @@ impl DataType {
             DataType::Bool => PhysicalType::Bool,
             DataType::Int8 => PhysicalType::Int8,
             DataType::Int16 => PhysicalType::Int16,
-            DataType::Int32 => PhysicalType::Int32,
-            DataType::Int64 | DataType::Timestamp => PhysicalType::Int64,
+            DataType::Int32 | DataType::Decimal32 => PhysicalType::Int32,
+            DataType::Int64 | DataType::Timestamp | DataType::Decimal64 => PhysicalType::Int64,
+            DataType::Decimal128 => PhysicalType::Int128,
             DataType::Float => PhysicalType::Float,
             DataType::Double => PhysicalType::Double,
             DataType::String | DataType::Binary => PhysicalType::Binary,
@@ -168,12 +243,14 @@ pub enum PhysicalType {
     Int16,
     Int32,
     Int64,
+    Int128,
     Float,
     Double,
     Binary,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum EncodingType {
     Auto,
     Plain,
@@ -210,6 +287,7 @@ impl EncodingType {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum CompressionType {
     Default,
     None,
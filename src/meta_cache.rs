@@ -21,7 +21,8 @@ use pb::ExpectField;
 use replica::{Replica, ReplicaRpc, ReplicaSet, Selection, Speculation};
 use retry::Retriable;
 use table::Table;
-use tablet::{Tablet, TabletReplica};
+use tablet::{Locality, Tablet, TabletReplica};
+use Client;
 use Error;
 use HostPort;
 use Options;
@@ -125,7 +126,7 @@ impl Entry {
 #[derive(Clone)]
 pub(crate) struct MetaCache {
     tables: Arc<Mutex<HashMap<TableId, TableLocations>>>,
-    tablet_servers: Arc<Mutex<HashMap<TabletServerId, krpc::Proxy>>>,
+    tablet_servers: Arc<Mutex<HashMap<TabletServerId, (krpc::Proxy, Arc<Locality>)>>>,
     masters: Arc<Box<[MasterReplica]>>,
     options: Options,
 }
@@ -238,6 +239,7 @@ impl MetaCache {
     pub(crate) fn open_table(
         &self,
         table: TableIdentifierPb,
+        client: Client,
         deadline: Instant,
     ) -> impl Future<Item = Table, Error = Error> {
         let call =
@@ -279,6 +281,7 @@ impl MetaCache {
                     partition_schema,
                     num_replicas,
                     table_locations,
+                    client,
                 ))
             })
     }
@@ -298,7 +301,7 @@ impl TableLocations {
         options: Options,
         table_id: TableId,
         masters: Arc<Box<[MasterReplica]>>,
-        tablet_servers: Arc<Mutex<HashMap<TabletServerId, krpc::Proxy>>>,
+        tablet_servers: Arc<Mutex<HashMap<TabletServerId, (krpc::Proxy, Arc<Locality>)>>>,
     ) -> TableLocations {
         let (sender, receiver) = mpsc::unbounded();
         let entries = Arc::new(Mutex::new(BTreeMap::new()));
@@ -327,6 +330,19 @@ impl TableLocations {
         })
     }
 
+    /// Removes the cached entry of a tablet which no longer exists at its cached location, so that
+    /// the next lookup in its partition key range requests fresh locations from the master.
+    pub(crate) fn invalidate_tablet(&self, tablet: &Tablet) {
+        let mut entries = self.entries.lock();
+        let is_cached = match entries.get(tablet.lower_bound()) {
+            Some(Entry::Tablet(ref cached)) => cached.id() == tablet.id(),
+            _ => false,
+        };
+        if is_cached {
+            entries.remove(tablet.lower_bound());
+        }
+    }
+
     fn extract<T>(&self, partition_key: &[u8], extractor: fn(&Entry) -> T) -> Lookup<T> {
         if let Some(entry) = get_entry(&self.entries.lock(), partition_key) {
             Lookup::Hit(Some(extractor(entry)))
@@ -397,7 +413,7 @@ struct TableLocationsTask {
     entries: Arc<Mutex<BTreeMap<PartitionKey, Entry>>>,
 
     /// Cache of tablet server connections.
-    tablet_servers: Arc<Mutex<HashMap<TabletServerId, krpc::Proxy>>>,
+    tablet_servers: Arc<Mutex<HashMap<TabletServerId, (krpc::Proxy, Arc<Locality>)>>>,
 
     table_id: TableId,
 
@@ -515,18 +531,21 @@ impl TableLocationsTask {
                         .collect::<Vec<_>>()
                         .into_boxed_slice();
 
-                    let proxy = self
+                    let (proxy, locality) = self
                         .tablet_servers
                         .lock()
                         .entry(id)
                         .or_insert_with(|| {
-                            krpc::Proxy::spawn(rpc_addrs.clone(), self.options.rpc.clone())
+                            let proxy =
+                                krpc::Proxy::spawn(rpc_addrs.clone(), self.options.rpc.clone());
+                            (proxy, Locality::new(&rpc_addrs))
                         }).clone();
 
                     Ok(TabletReplica {
                         id,
                         rpc_addrs,
                         proxy,
+                        locality,
                         is_stale: AtomicBool::new(false),
                         is_leader: AtomicBool::new(is_leader),
                     })
@@ -8,17 +8,28 @@ use std::process::{Child, Command, Stdio};
 use std::thread;
 
 use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use futures::future;
 use prost::Message;
 use tempdir::TempDir;
+use tokio::runtime::current_thread::Runtime;
 
 use pb::tools::control_shell_request_pb::Request;
 use pb::tools::control_shell_response_pb::Response;
 use pb::tools::{
     ControlShellRequestPb, ControlShellResponsePb, CreateClusterRequestPb, DaemonIdentifierPb,
-    DaemonType, GetMastersRequestPb, GetMastersResponsePb, StartClusterRequestPb,
-    StartDaemonRequestPb, StopClusterRequestPb, StopDaemonRequestPb,
+    DaemonType, GetMastersRequestPb, GetMastersResponsePb, GetTServersRequestPb,
+    GetTServersResponsePb, StartClusterRequestPb, StartDaemonRequestPb, StopClusterRequestPb,
+    StopDaemonRequestPb,
 };
+use Client;
+use Column;
+use DataType;
 use HostPort;
+use Row;
+use SchemaBuilder;
+use Table;
+use TableBuilder;
+use WriterConfig;
 
 pub struct MiniCluster {
     data_root: TempDir,
@@ -82,6 +93,20 @@ impl MiniCluster {
         }
     }
 
+    /// Returns the RPC addresses of the tablet servers, in index order.
+    pub fn tserver_addrs(&mut self) -> Vec<HostPort> {
+        let response = self.send_request(Request::GetTservers(GetTServersRequestPb::default()));
+
+        if let Some(Response::GetTservers(GetTServersResponsePb { tservers })) = response {
+            tservers
+                .into_iter()
+                .map(|tserver| tserver.bound_rpc_address.unwrap().into())
+                .collect()
+        } else {
+            panic!("unexpected response: {:?}", response)
+        }
+    }
+
     pub fn stop_master(&mut self, index: u32) {
         let mut id = DaemonIdentifierPb::default();
         id.set_type_(DaemonType::Master);
@@ -96,6 +121,20 @@ impl MiniCluster {
         self.send_request(Request::StartDaemon(StartDaemonRequestPb { id: Some(id) }));
     }
 
+    pub fn stop_tserver(&mut self, index: u32) {
+        let mut id = DaemonIdentifierPb::default();
+        id.set_type_(DaemonType::Tserver);
+        id.index = Some(index);
+        self.send_request(Request::StopDaemon(StopDaemonRequestPb { id: Some(id) }));
+    }
+
+    pub fn start_tserver(&mut self, index: u32) {
+        let mut id = DaemonIdentifierPb::default();
+        id.set_type_(DaemonType::Tserver);
+        id.index = Some(index);
+        self.send_request(Request::StartDaemon(StartDaemonRequestPb { id: Some(id) }));
+    }
+
     fn send_request(&mut self, request: Request) -> Option<Response> {
         let stdin = self.process.stdin.as_mut().expect("stdin");
         let stdout = self.process.stdout.as_mut().expect("stdout");
@@ -210,6 +249,18 @@ impl MiniClusterConfig {
         self.push_flag("--enable-leader-failure-detection=false".to_string())
     }
 
+    /// Sets the time after which idle scanners expire. Expired scanners are collected every 100
+    /// milliseconds.
+    pub fn scanner_ttl(&mut self, millis: i32) -> &mut MiniClusterConfig {
+        self.pb
+            .extra_tserver_flags
+            .push(format!("--scanner-ttl-ms={}", millis));
+        self.pb
+            .extra_tserver_flags
+            .push("--scanner-gc-check-interval-us=100000".to_string());
+        self
+    }
+
     pub fn disable_data_block_fsync(&mut self) -> &mut MiniClusterConfig {
         self.push_flag("--enable-data-block-fsync=false".to_string())
     }
@@ -229,6 +280,69 @@ pub enum LogLevel {
     Fatal = 3,
 }
 
+/// Creates a table, and opens it.
+pub fn create_table(runtime: &mut Runtime, client: &mut Client, builder: TableBuilder) -> Table {
+    let table_id = runtime.block_on(client.create_table(builder)).unwrap();
+    runtime.block_on(client.open_table_by_id(table_id)).unwrap()
+}
+
+/// Inserts `num_rows` rows into a table, and flushes them. `fill` sets the columns of each row
+/// from its index.
+pub fn insert_rows<F>(runtime: &mut Runtime, table: &Table, num_rows: i32, mut fill: F)
+where
+    F: FnMut(&mut Row, i32),
+{
+    let mut writer = table.new_writer(WriterConfig::default());
+    // TODO: remove lazy once apply no longer polls.
+    runtime
+        .block_on(future::lazy::<_, Result<(), ()>>(|| {
+            for i in 0..num_rows {
+                let mut insert = table.schema().new_row();
+                fill(&mut insert, i);
+                writer.insert(insert);
+            }
+            Ok(())
+        })).unwrap();
+    let stats = runtime
+        .block_on(future::poll_fn(|| writer.poll_flush()))
+        .unwrap();
+    assert_eq!(num_rows as usize, stats.successful_operations());
+}
+
+/// Creates a table with an `Int32` primary key column `key` and a nullable `Int32` column `val`,
+/// and inserts `num_rows` rows with `key` and `val` set to `0` through `num_rows - 1`.
+///
+/// The table has a single replica, unless `partitioning` sets the number of replicas along with
+/// the partitioning of the table.
+pub fn create_table_with_rows<F>(
+    runtime: &mut Runtime,
+    client: &mut Client,
+    name: &str,
+    partitioning: F,
+    num_rows: i32,
+) -> Table
+where
+    F: FnOnce(&mut TableBuilder),
+{
+    let schema = SchemaBuilder::new()
+        .add_column(Column::new("key", DataType::Int32).set_not_null())
+        .add_column(Column::new("val", DataType::Int32))
+        .set_primary_key(vec!["key"])
+        .build()
+        .unwrap();
+
+    let mut builder = TableBuilder::new(name, schema);
+    builder.set_num_replicas(1);
+    partitioning(&mut builder);
+
+    let table = create_table(runtime, client, builder);
+    insert_rows(runtime, &table, num_rows, |row, i| {
+        row.set("key", i).unwrap();
+        row.set("val", i).unwrap();
+    });
+    table
+}
+
 #[cfg(test)]
 mod tests {
 
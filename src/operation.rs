@@ -12,6 +12,8 @@ use Error;
 use RangePartitionBound;
 use Row;
 use Schema;
+use Status;
+use StatusCode;
 use Value;
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -20,15 +22,35 @@ pub enum OperationKind {
     Update,
     Upsert,
     Delete,
+    /// An insert which succeeds if a row with the same primary key already exists.
+    InsertIgnore,
+    /// An update which succeeds if the row does not exist.
+    UpdateIgnore,
+    /// A delete which succeeds if the row does not exist.
+    DeleteIgnore,
 }
 
 impl OperationKind {
     pub(crate) fn as_pb(self) -> OperationTypePb {
         match self {
-            OperationKind::Insert => OperationTypePb::Insert,
-            OperationKind::Update => OperationTypePb::Update,
+            OperationKind::Insert | OperationKind::InsertIgnore => OperationTypePb::Insert,
+            OperationKind::Update | OperationKind::UpdateIgnore => OperationTypePb::Update,
             OperationKind::Upsert => OperationTypePb::Upsert,
-            OperationKind::Delete => OperationTypePb::Delete,
+            OperationKind::Delete | OperationKind::DeleteIgnore => OperationTypePb::Delete,
+        }
+    }
+
+    /// Returns `true` if a row error with the status code is ignored for the operation kind.
+    ///
+    /// Ignored errors are filtered out by the writer, since tablet servers do not support the
+    /// ignore operation kinds.
+    pub(crate) fn ignores(self, code: StatusCode) -> bool {
+        match self {
+            OperationKind::InsertIgnore => code == StatusCode::AlreadyPresent,
+            OperationKind::UpdateIgnore | OperationKind::DeleteIgnore => {
+                code == StatusCode::NotFound
+            }
+            _ => false,
         }
     }
 }
@@ -48,12 +70,25 @@ impl<'data> Operation<'data> {
     }
 }
 
+/// A row operation which failed, along with the error.
+#[derive(Debug)]
 pub struct OperationError {
     pub row: Row<'static>,
     pub kind: OperationKind,
     pub error: Error,
 }
 
+impl OperationError {
+    /// Returns the status returned by the tablet server, or `None` if the operation failed before
+    /// being sent to a tablet server.
+    pub fn status(&self) -> Option<&Status> {
+        match self.error {
+            Error::RowError(ref status) => Some(status),
+            _ => None,
+        }
+    }
+}
+
 pub(crate) struct OperationEncoder {
     pub(crate) data: Vec<u8>,
     pub(crate) indirect_data: Vec<u8>,
@@ -234,12 +269,15 @@ impl<'a> Iterator for OperationDecoder<'a> {
                     DataType::Int16 => {
                         row.set_unchecked(idx, i16::read_cell(data).unwrap());
                     }
-                    DataType::Int32 => {
+                    DataType::Int32 | DataType::Decimal32 => {
                         row.set_unchecked(idx, i32::read_cell(data).unwrap());
                     }
-                    DataType::Int64 | DataType::Timestamp => {
+                    DataType::Int64 | DataType::Timestamp | DataType::Decimal64 => {
                         row.set_unchecked(idx, i64::read_cell(data).unwrap());
                     }
+                    DataType::Decimal128 => {
+                        row.set_unchecked(idx, i128::read_cell(data).unwrap());
+                    }
                     DataType::Float => {
                         row.set_unchecked(idx, f32::read_cell(data).unwrap());
                     }
@@ -83,6 +83,23 @@ impl PartitionSchema {
         &self.inner.hash_partitions
     }
 
+    #[cfg(test)]
+    pub(crate) fn new(
+        range_columns: Vec<usize>,
+        hash_partitions: Vec<(Vec<usize>, u32, u32)>,
+    ) -> PartitionSchema {
+        let hash_partitions = hash_partitions
+            .into_iter()
+            .map(|(columns, buckets, seed)| HashPartitionSchema::new(columns, buckets, seed))
+            .collect::<Vec<_>>();
+        PartitionSchema {
+            inner: Arc::new(Inner {
+                range_partition: RangePartitionSchema::new(range_columns),
+                hash_partitions: hash_partitions.into_boxed_slice(),
+            }),
+        }
+    }
+
     pub(crate) fn from_pb(pb: &PartitionSchemaPb, schema: &SchemaPb) -> PartitionSchema {
         let mut columns_by_name = HashMap::new();
         let mut columns_by_id = HashMap::new();
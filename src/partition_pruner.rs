@@ -0,0 +1,533 @@
+use std::mem;
+
+use byteorder::{BigEndian, WriteBytesExt};
+use vec_map::VecMap;
+
+use key;
+use Filter;
+use HashPartitionSchema;
+use PartitionSchema;
+use RangePartitionBound;
+use RangePartitionSchema;
+use Schema;
+
+/// The maximum number of hash column value combinations which will be hashed in order to prune
+/// hash partitions. Filters with more combinations do not prune hash partitions.
+const MAX_HASH_VALUE_COMBINATIONS: usize = 4096;
+
+/// Computes the partition key ranges of a table which may hold rows matching a set of filters.
+#[derive(Clone, Debug)]
+pub(crate) struct PartitionPruner {
+    /// Sorted, non-overlapping partition key ranges. The lower bounds are inclusive, and the upper
+    /// bounds are exclusive. An empty upper bound is unbounded.
+    ranges: Vec<(Vec<u8>, Vec<u8>)>,
+}
+
+impl PartitionPruner {
+    /// Creates a new partition pruner from a set of filters indexed by column, and optional
+    /// primary key bounds.
+    pub(crate) fn new(
+        schema: &Schema,
+        partition_schema: &PartitionSchema,
+        filters: &VecMap<Filter>,
+        lower_bound: Option<&RangePartitionBound>,
+        upper_bound: Option<&RangePartitionBound>,
+    ) -> PartitionPruner {
+        let range_schema = partition_schema.range_partition_schema();
+        let (mut range_lower, mut range_upper) = range_bounds(schema, range_schema, filters);
+
+        // Intersect the filter bounds with the primary key bounds.
+        let (key_lower, key_upper) =
+            primary_key_range_bounds(schema, range_schema, lower_bound, upper_bound);
+        if key_lower > range_lower {
+            range_lower = key_lower;
+        }
+        if !key_upper.is_empty() && (range_upper.is_empty() || key_upper < range_upper) {
+            range_upper = key_upper;
+        }
+
+        if !range_upper.is_empty() && range_lower >= range_upper {
+            return PartitionPruner { ranges: Vec::new() };
+        }
+
+        // Compute the hash bucket prefixes of the partition key ranges. Unconstrained hash
+        // partition levels are expanded to every bucket if a following level or the range bounds
+        // are constrained. Trailing unconstrained levels are left out of the prefixes.
+        let hash_schemas = partition_schema.hash_partition_schemas();
+        let buckets = hash_schemas
+            .iter()
+            .map(|hash_schema| hash_buckets(schema, hash_schema, filters))
+            .collect::<Vec<_>>();
+        let num_levels = if !range_lower.is_empty() || !range_upper.is_empty() {
+            hash_schemas.len()
+        } else {
+            buckets
+                .iter()
+                .rposition(Option::is_some)
+                .map_or(0, |level| level + 1)
+        };
+        let is_constrained = num_levels == hash_schemas.len();
+
+        let mut prefixes = vec![Vec::new()];
+        for (hash_schema, buckets) in hash_schemas.iter().zip(buckets).take(num_levels) {
+            let buckets =
+                buckets.unwrap_or_else(|| (0..hash_schema.num_buckets()).collect::<Vec<_>>());
+
+            let mut next_prefixes = Vec::with_capacity(prefixes.len() * buckets.len());
+            for prefix in &prefixes {
+                for &bucket in &buckets {
+                    let mut next_prefix = prefix.clone();
+                    next_prefix.write_u32::<BigEndian>(bucket).unwrap();
+                    next_prefixes.push(next_prefix);
+                }
+            }
+            prefixes = next_prefixes;
+        }
+
+        let mut ranges: Vec<(Vec<u8>, Vec<u8>)> = Vec::with_capacity(prefixes.len());
+        for prefix in prefixes {
+            let (lower, upper) = if is_constrained {
+                let mut lower = prefix.clone();
+                lower.extend_from_slice(&range_lower);
+                let upper = if range_upper.is_empty() {
+                    increment(prefix)
+                } else {
+                    let mut upper = prefix;
+                    upper.extend_from_slice(&range_upper);
+                    upper
+                };
+                (lower, upper)
+            } else {
+                (prefix.clone(), increment(prefix))
+            };
+
+            // Coalesce overlapping and adjacent ranges.
+            if let Some(last) = ranges.last_mut() {
+                if last.1.is_empty() {
+                    continue;
+                }
+                if last.1 >= lower {
+                    if upper.is_empty() || upper > last.1 {
+                        last.1 = upper;
+                    }
+                    continue;
+                }
+            }
+            ranges.push((lower, upper));
+        }
+
+        PartitionPruner { ranges }
+    }
+
+    /// Creates a partition pruner which prunes every partition.
+    pub(crate) fn empty() -> PartitionPruner {
+        PartitionPruner { ranges: Vec::new() }
+    }
+
+    /// Restricts the pruner to a partition key range. The lower bound is inclusive, and the upper
+    /// bound is exclusive. An empty upper bound is unbounded.
+    pub(crate) fn intersect_partition_key_range(&mut self, lower: &[u8], upper: &[u8]) {
+        let ranges = mem::replace(&mut self.ranges, Vec::new());
+        for (mut range_lower, mut range_upper) in ranges {
+            if &range_lower[..] < lower {
+                range_lower = lower.to_owned();
+            }
+            if !upper.is_empty() && (range_upper.is_empty() || upper < &range_upper[..]) {
+                range_upper = upper.to_owned();
+            }
+            if range_upper.is_empty() || range_lower < range_upper {
+                self.ranges.push((range_lower, range_upper));
+            }
+        }
+    }
+
+    /// Returns `true` if no partition may hold matching rows.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Returns the first partition key at or after `partition_key` which may hold matching rows,
+    /// or `None` if no remaining partitions may hold matching rows.
+    pub(crate) fn next_partition_key<'a>(&'a self, partition_key: &'a [u8]) -> Option<&'a [u8]> {
+        for &(ref lower, ref upper) in &self.ranges {
+            if upper.is_empty() || partition_key < &upper[..] {
+                return Some(if partition_key > &lower[..] {
+                    partition_key
+                } else {
+                    lower
+                });
+            }
+        }
+        None
+    }
+}
+
+/// Returns the inclusive lower and exclusive upper range partition key bounds of the rows matching
+/// the filters. Empty bounds are unbounded.
+fn range_bounds(
+    schema: &Schema,
+    range_schema: &RangePartitionSchema,
+    filters: &VecMap<Filter>,
+) -> (Vec<u8>, Vec<u8>) {
+    let columns = range_schema.columns();
+
+    let mut lower = Vec::new();
+    for (i, &idx) in columns.iter().enumerate() {
+        let data_type = schema.columns()[idx].data_type();
+        let is_last = i + 1 == columns.len();
+        match filters.get(idx) {
+            Some(&Filter::Equals { ref value, .. }) => {
+                key::encode_value(data_type, value, is_last, &mut lower);
+            }
+            Some(&Filter::Range {
+                lower_bound: Some(ref value),
+                ..
+            }) => {
+                key::encode_value(data_type, value, is_last, &mut lower);
+                break;
+            }
+            Some(&Filter::In { ref values, .. }) if !values.is_empty() => {
+                key::encode_value(data_type, &values[0], is_last, &mut lower);
+                break;
+            }
+            _ => break,
+        }
+    }
+
+    // The upper bound is built from a prefix of the range columns. If the prefix is inclusive, it
+    // must be converted to an exclusive bound by incrementing it.
+    let mut upper = Vec::new();
+    let mut encoded_columns = 0;
+    let mut is_inclusive = false;
+    for (i, &idx) in columns.iter().enumerate() {
+        let data_type = schema.columns()[idx].data_type();
+        let is_last = i + 1 == columns.len();
+        match filters.get(idx) {
+            Some(&Filter::Equals { ref value, .. }) => {
+                key::encode_value(data_type, value, is_last, &mut upper);
+                encoded_columns += 1;
+                is_inclusive = true;
+            }
+            Some(&Filter::Range {
+                upper_bound: Some(ref value),
+                ..
+            }) => {
+                key::encode_value(data_type, value, is_last, &mut upper);
+                is_inclusive = false;
+                break;
+            }
+            Some(&Filter::In { ref values, .. }) if !values.is_empty() => {
+                key::encode_value(data_type, values.last().unwrap(), is_last, &mut upper);
+                encoded_columns += 1;
+                is_inclusive = true;
+                break;
+            }
+            _ => break,
+        }
+    }
+
+    if is_inclusive {
+        if encoded_columns == columns.len() {
+            // The key is complete, so the immediate successor is the exclusive bound.
+            upper.push(0);
+        } else {
+            upper = increment(upper);
+        }
+    }
+
+    (lower, upper)
+}
+
+/// Returns the inclusive lower and exclusive upper range partition key bounds of the rows within
+/// the primary key bounds. The bounds only apply if the range partition columns are a prefix of
+/// the primary key columns. Empty bounds are unbounded.
+fn primary_key_range_bounds(
+    schema: &Schema,
+    range_schema: &RangePartitionSchema,
+    lower_bound: Option<&RangePartitionBound>,
+    upper_bound: Option<&RangePartitionBound>,
+) -> (Vec<u8>, Vec<u8>) {
+    let columns = range_schema.columns();
+    let is_prefix = !columns.is_empty()
+        && columns.len() <= schema.num_primary_key_columns()
+        && columns.iter().enumerate().all(|(i, &idx)| i == idx);
+    if !is_prefix {
+        return (Vec::new(), Vec::new());
+    }
+    let is_primary_key = columns.len() == schema.num_primary_key_columns();
+
+    let mut lower = Vec::new();
+    if let Some(bound) = lower_bound {
+        key::encode_range_partition_key(range_schema, bound.row(), &mut lower)
+            .expect("primary key bound is missing a primary key column");
+    }
+
+    let mut upper = Vec::new();
+    if let Some(bound) = upper_bound {
+        key::encode_range_partition_key(range_schema, bound.row(), &mut upper)
+            .expect("primary key bound is missing a primary key column");
+        // Rows sharing the range partition columns of the bound may fall below the bound, unless
+        // the range partition key is the full primary key and the bound is exclusive.
+        match *bound {
+            RangePartitionBound::Exclusive(_) if is_primary_key => (),
+            _ => upper.push(0),
+        }
+    }
+
+    (lower, upper)
+}
+
+/// Returns the sorted hash buckets of the rows matching the filters, or `None` if the hash
+/// buckets are unconstrained.
+fn hash_buckets(
+    schema: &Schema,
+    hash_schema: &HashPartitionSchema,
+    filters: &VecMap<Filter>,
+) -> Option<Vec<u32>> {
+    let columns = hash_schema.columns();
+
+    let mut column_values: Vec<Vec<&[u8]>> = Vec::with_capacity(columns.len());
+    let mut combinations = 1usize;
+    for &idx in columns {
+        let values = match filters.get(idx) {
+            Some(&Filter::Equals { ref value, .. }) => vec![&value[..]],
+            Some(&Filter::In { ref values, .. }) => {
+                values.iter().map(|value| &value[..]).collect()
+            }
+            _ => return None,
+        };
+        combinations = combinations.saturating_mul(values.len());
+        column_values.push(values);
+    }
+
+    if combinations == 0 {
+        return Some(Vec::new());
+    } else if combinations > MAX_HASH_VALUE_COMBINATIONS {
+        return None;
+    }
+
+    let mut buckets = Vec::with_capacity(combinations);
+    let mut indexes = vec![0; columns.len()];
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        for (i, &idx) in columns.iter().enumerate() {
+            key::encode_value(
+                schema.columns()[idx].data_type(),
+                column_values[i][indexes[i]],
+                i + 1 == columns.len(),
+                &mut buf,
+            );
+        }
+        buckets.push(key::hash_bucket(hash_schema, &buf));
+
+        // Advance to the next combination of values.
+        let mut i = columns.len();
+        loop {
+            if i == 0 {
+                buckets.sort_unstable();
+                buckets.dedup();
+                return Some(buckets);
+            }
+            i -= 1;
+            indexes[i] += 1;
+            if indexes[i] < column_values[i].len() {
+                break;
+            }
+            indexes[i] = 0;
+        }
+    }
+}
+
+/// Returns the smallest key which is greater than every key prefixed by `key`, or an empty key if
+/// no such key exists.
+fn increment(mut key: Vec<u8>) -> Vec<u8> {
+    while let Some(byte) = key.pop() {
+        if byte < 0xff {
+            key.push(byte + 1);
+            break;
+        }
+    }
+    key
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    use key;
+    use Column;
+    use DataType;
+    use Row;
+    use SchemaBuilder;
+
+    fn schema() -> Schema {
+        SchemaBuilder::new()
+            .add_column(Column::new("a", DataType::Int32).set_not_null())
+            .add_column(Column::new("b", DataType::String).set_not_null())
+            .add_column(Column::new("c", DataType::Int64))
+            .set_primary_key(vec!["a", "b"])
+            .build()
+            .unwrap()
+    }
+
+    fn row(schema: &Schema, a: i32, b: &str) -> Row<'static> {
+        let mut row = schema.new_row();
+        row.set(0, a).unwrap();
+        row.set(1, b.to_owned()).unwrap();
+        row
+    }
+
+    fn contains(
+        pruner: &PartitionPruner,
+        partition_schema: &PartitionSchema,
+        row: &Row,
+    ) -> bool {
+        let partition_key = key::encode_partition_key(partition_schema, row).unwrap();
+        pruner.next_partition_key(&partition_key) == Some(&partition_key[..])
+    }
+
+    #[test]
+    fn unfiltered() {
+        let schema = schema();
+        let partition_schema = PartitionSchema::new(vec![0, 1], vec![(vec![0], 4, 0)]);
+        let pruner = PartitionPruner::new(&schema, &partition_schema, &VecMap::new(), None, None);
+        assert_eq!(vec![(Vec::new(), Vec::new())], pruner.ranges);
+        assert_eq!(Some(&b""[..]), pruner.next_partition_key(b""));
+    }
+
+    #[test]
+    fn hash_equals() {
+        let schema = schema();
+        let partition_schema = PartitionSchema::new(vec![], vec![(vec![0], 16, 0)]);
+        let mut filters = VecMap::new();
+        filters.insert(0, Filter::equals(7i32));
+        let pruner = PartitionPruner::new(&schema, &partition_schema, &filters, None, None);
+        assert_eq!(1, pruner.ranges.len());
+        assert!(contains(&pruner, &partition_schema, &row(&schema, 7, "")));
+        assert!(contains(&pruner, &partition_schema, &row(&schema, 7, "foo")));
+    }
+
+    #[test]
+    fn hash_in_list() {
+        let schema = schema();
+        let partition_schema =
+            PartitionSchema::new(vec![0, 1], vec![(vec![0], 16, 0), (vec![1], 8, 42)]);
+        let mut filters = VecMap::new();
+        filters.insert(0, Filter::in_list(vec![1i32, 2, 3]));
+        filters.insert(1, Filter::equals("foo".to_owned()));
+        let pruner = PartitionPruner::new(&schema, &partition_schema, &filters, None, None);
+        assert!(!pruner.is_empty());
+        assert!(pruner.ranges.len() <= 3);
+        for a in 1..4 {
+            assert!(contains(&pruner, &partition_schema, &row(&schema, a, "foo")));
+        }
+    }
+
+    #[test]
+    fn range() {
+        let schema = schema();
+        let partition_schema = PartitionSchema::new(vec![0, 1], vec![]);
+        let mut filters = VecMap::new();
+        filters.insert(0, Filter::range(10i32..20));
+        let pruner = PartitionPruner::new(&schema, &partition_schema, &filters, None, None);
+        assert_eq!(1, pruner.ranges.len());
+        assert!(!contains(&pruner, &partition_schema, &row(&schema, 9, "zzz")));
+        assert!(contains(&pruner, &partition_schema, &row(&schema, 10, "")));
+        assert!(contains(&pruner, &partition_schema, &row(&schema, 19, "zzz")));
+        assert!(!contains(&pruner, &partition_schema, &row(&schema, 20, "")));
+    }
+
+    #[test]
+    fn range_prefix_equals() {
+        let schema = schema();
+        let partition_schema = PartitionSchema::new(vec![0, 1], vec![]);
+        let mut filters = VecMap::new();
+        filters.insert(0, Filter::equals(5i32));
+        let pruner = PartitionPruner::new(&schema, &partition_schema, &filters, None, None);
+        assert!(!contains(&pruner, &partition_schema, &row(&schema, 4, "zzz")));
+        assert!(contains(&pruner, &partition_schema, &row(&schema, 5, "")));
+        assert!(contains(&pruner, &partition_schema, &row(&schema, 5, "zzz")));
+        assert!(!contains(&pruner, &partition_schema, &row(&schema, 6, "")));
+    }
+
+    #[test]
+    fn primary_key_bounds() {
+        let schema = schema();
+        let partition_schema = PartitionSchema::new(vec![0, 1], vec![]);
+        let lower = RangePartitionBound::Inclusive(row(&schema, 10, "a"));
+        let upper = RangePartitionBound::Exclusive(row(&schema, 20, "b"));
+        let pruner = PartitionPruner::new(
+            &schema,
+            &partition_schema,
+            &VecMap::new(),
+            Some(&lower),
+            Some(&upper),
+        );
+        assert_eq!(1, pruner.ranges.len());
+        assert!(!contains(&pruner, &partition_schema, &row(&schema, 10, "")));
+        assert!(contains(&pruner, &partition_schema, &row(&schema, 10, "a")));
+        assert!(contains(&pruner, &partition_schema, &row(&schema, 20, "a")));
+        assert!(!contains(&pruner, &partition_schema, &row(&schema, 20, "b")));
+
+        // The bounds only constrain a prefix of the range partition key.
+        let partition_schema = PartitionSchema::new(vec![0], vec![(vec![1], 4, 0)]);
+        let pruner = PartitionPruner::new(
+            &schema,
+            &partition_schema,
+            &VecMap::new(),
+            Some(&lower),
+            Some(&upper),
+        );
+        assert!(!contains(&pruner, &partition_schema, &row(&schema, 9, "zzz")));
+        assert!(contains(&pruner, &partition_schema, &row(&schema, 10, "")));
+        assert!(contains(&pruner, &partition_schema, &row(&schema, 20, "zzz")));
+        assert!(!contains(&pruner, &partition_schema, &row(&schema, 21, "")));
+
+        // Primary key bounds do not apply to range partition columns outside of the primary key
+        // prefix.
+        let partition_schema = PartitionSchema::new(vec![1], vec![]);
+        let pruner = PartitionPruner::new(
+            &schema,
+            &partition_schema,
+            &VecMap::new(),
+            Some(&lower),
+            Some(&upper),
+        );
+        assert_eq!(vec![(Vec::new(), Vec::new())], pruner.ranges);
+    }
+
+    #[test]
+    fn partition_key_range() {
+        let schema = schema();
+        let partition_schema = PartitionSchema::new(vec![0, 1], vec![]);
+        let mut filters = VecMap::new();
+        filters.insert(0, Filter::range(10i32..20));
+        let mut pruner = PartitionPruner::new(&schema, &partition_schema, &filters, None, None);
+
+        let lower = key::encode_partition_key(&partition_schema, &row(&schema, 15, "")).unwrap();
+        pruner.intersect_partition_key_range(&lower, &[]);
+        assert!(!contains(&pruner, &partition_schema, &row(&schema, 14, "zzz")));
+        assert!(contains(&pruner, &partition_schema, &row(&schema, 15, "")));
+        assert!(contains(&pruner, &partition_schema, &row(&schema, 19, "zzz")));
+
+        pruner.intersect_partition_key_range(&[], &lower);
+        assert!(pruner.is_empty());
+    }
+
+    #[test]
+    fn hash_and_range() {
+        let schema = schema();
+        let partition_schema = PartitionSchema::new(vec![0], vec![(vec![1], 4, 0)]);
+        let mut filters = VecMap::new();
+        filters.insert(0, Filter::range(0i32..10));
+        filters.insert(1, Filter::equals("foo".to_owned()));
+        let pruner = PartitionPruner::new(&schema, &partition_schema, &filters, None, None);
+        assert_eq!(1, pruner.ranges.len());
+        assert!(contains(&pruner, &partition_schema, &row(&schema, 0, "foo")));
+        assert!(contains(&pruner, &partition_schema, &row(&schema, 9, "foo")));
+        assert!(!contains(&pruner, &partition_schema, &row(&schema, 10, "foo")));
+        assert!(!contains(&pruner, &partition_schema, &row(&schema, -1, "foo")));
+    }
+}
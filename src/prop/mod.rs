@@ -25,6 +25,9 @@ impl DataType {
                 DataType::Double,
                 DataType::String,
                 DataType::Binary,
+                DataType::Decimal32,
+                DataType::Decimal64,
+                DataType::Decimal128,
             ][..],
         )
     }
@@ -39,6 +42,9 @@ impl DataType {
                 DataType::Timestamp,
                 DataType::String,
                 DataType::Binary,
+                DataType::Decimal32,
+                DataType::Decimal64,
+                DataType::Decimal128,
             ][..],
         )
     }
@@ -46,35 +52,7 @@ impl DataType {
 
 impl EncodingType {
     pub fn arbitrary(data_type: DataType) -> impl Strategy<Value = EncodingType> {
-        match data_type {
-            DataType::Int8
-            | DataType::Int16
-            | DataType::Int32
-            | DataType::Int64
-            | DataType::Timestamp => sample::select(
-                &[
-                    EncodingType::Auto,
-                    EncodingType::Plain,
-                    EncodingType::BitShuffle,
-                    EncodingType::RunLength,
-                ][..],
-            ),
-            DataType::Bool | DataType::Float | DataType::Double => sample::select(
-                &[
-                    EncodingType::Auto,
-                    EncodingType::Plain,
-                    EncodingType::RunLength,
-                ][..],
-            ),
-            DataType::String | DataType::Binary => sample::select(
-                &[
-                    EncodingType::Auto,
-                    EncodingType::Plain,
-                    EncodingType::Dictionary,
-                    EncodingType::RunLength,
-                ][..],
-            ),
-        }
+        sample::select(data_type.encodings())
     }
 }
 
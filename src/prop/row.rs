@@ -59,6 +59,21 @@ impl Strategy for RowStrategy {
                                 .collect::<Vec<_>>();
                             row.set_unchecked(idx, bytes)
                         }
+                        DataType::Decimal32 => {
+                            let max = 10i32.pow(u32::from(column.precision().unwrap())) - 1;
+                            row.set_unchecked(idx, rng.gen_range(-max, max + 1))
+                        }
+                        DataType::Decimal64 => {
+                            let max = 10i64.pow(u32::from(column.precision().unwrap())) - 1;
+                            row.set_unchecked(idx, rng.gen_range(-max, max + 1))
+                        }
+                        DataType::Decimal128 => {
+                            // rand does not support generating 128-bit integers in a range.
+                            let max = 10i128.pow(u32::from(column.precision().unwrap())) - 1;
+                            let value = i128::from(rng.gen::<u64>()) << 64
+                                | i128::from(rng.gen::<u64>());
+                            row.set_unchecked(idx, value % (max + 1))
+                        }
                     };
                 }
             }
@@ -122,6 +137,8 @@ enum ColumnTree {
     Int16(num::i16::BinarySearch),
     Int32(num::i32::BinarySearch),
     Int64(num::i64::BinarySearch),
+    /// proptest does not support 128-bit integers, so `Decimal128` values are not simplified.
+    Decimal128(i128),
     Float(num::f32::BinarySearch),
     Double(num::f64::BinarySearch),
     Binary(binary::BinarySearch),
@@ -138,12 +155,13 @@ impl ColumnTree {
             DataType::Int16 => {
                 ColumnTree::Int16(num::i16::BinarySearch::new(row.get(idx).unwrap()))
             }
-            DataType::Int32 => {
+            DataType::Int32 | DataType::Decimal32 => {
                 ColumnTree::Int32(num::i32::BinarySearch::new(row.get(idx).unwrap()))
             }
-            DataType::Int64 | DataType::Timestamp => {
+            DataType::Int64 | DataType::Timestamp | DataType::Decimal64 => {
                 ColumnTree::Int64(num::i64::BinarySearch::new(row.get(idx).unwrap()))
             }
+            DataType::Decimal128 => ColumnTree::Decimal128(row.get(idx).unwrap()),
             DataType::Float => {
                 ColumnTree::Float(num::f32::BinarySearch::new(row.get(idx).unwrap()))
             }
@@ -166,6 +184,7 @@ impl ColumnTree {
             ColumnTree::Int16(tree) => tree.simplify(),
             ColumnTree::Int32(tree) => tree.simplify(),
             ColumnTree::Int64(tree) => tree.simplify(),
+            ColumnTree::Decimal128(_) => false,
             ColumnTree::Float(tree) => tree.simplify(),
             ColumnTree::Double(tree) => tree.simplify(),
             ColumnTree::Binary(tree) => tree.simplify(),
@@ -180,6 +199,7 @@ impl ColumnTree {
             ColumnTree::Int16(tree) => tree.complicate(),
             ColumnTree::Int32(tree) => tree.complicate(),
             ColumnTree::Int64(tree) => tree.complicate(),
+            ColumnTree::Decimal128(_) => false,
             ColumnTree::Float(tree) => tree.complicate(),
             ColumnTree::Double(tree) => tree.complicate(),
             ColumnTree::Binary(tree) => tree.complicate(),
@@ -195,6 +215,7 @@ impl ColumnTree {
                 ColumnTree::Int16(tree) => row.set_unchecked(idx, tree.current()),
                 ColumnTree::Int32(tree) => row.set_unchecked(idx, tree.current()),
                 ColumnTree::Int64(tree) => row.set_unchecked(idx, tree.current()),
+                ColumnTree::Decimal128(value) => row.set_unchecked(idx, *value),
                 ColumnTree::Float(tree) => row.set_unchecked(idx, tree.current()),
                 ColumnTree::Double(tree) => row.set_unchecked(idx, tree.current()),
                 ColumnTree::Binary(tree) => row.set_unchecked(idx, tree.current()),
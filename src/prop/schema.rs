@@ -25,16 +25,25 @@ impl Column {
                 (
                     Just(data_type),
                     EncodingType::arbitrary(data_type).no_shrink(),
+                    Column::arbitrary_precision_and_scale(data_type),
                 )
             }).prop_ind_flat_map2(|_| CompressionType::arbitrary().no_shrink())
             .prop_ind_flat_map2(|_| "[a-z0-9_]{1, 16}".no_shrink())
-            .prop_map(|(((data_type, encoding), compression), name)| {
+            .prop_map(|(((data_type, encoding, (precision, scale)), compression), name)| {
                 Column::new(name, data_type)
                     .set_is_nullable(false)
                     .set_compression(compression)
                     .set_encoding(encoding)
+                    .set_precision(precision)
+                    .set_scale(scale)
             })
     }
+    /// Returns a strategy for the precision and scale of a column of the data type, which are
+    /// always 0 for non-decimal data types.
+    fn arbitrary_precision_and_scale(data_type: DataType) -> impl Strategy<Value = (u8, u8)> {
+        let (min, max) = data_type.decimal_precision_range().unwrap_or((0, 0));
+        (min..max + 1).prop_flat_map(|precision| (Just(precision), 0..precision + 1))
+    }
     pub fn arbitrary() -> impl Strategy<Value = Column> {
         Column::arbitrary_with(DataType::arbitrary(), proptest::bool::ANY)
     }
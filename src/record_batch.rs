@@ -0,0 +1,337 @@
+//! Conversions from scan results to Apache Arrow record batches.
+
+use std::sync::Arc;
+
+use arrow::array::ArrayRef;
+use arrow::builder::{
+    BinaryBuilder, BooleanBuilder, Float32Builder, Float64Builder, Int16Builder, Int32Builder,
+    Int64Builder, Int8Builder, ListBuilder, TimestampMicrosecondBuilder, UInt8Builder,
+};
+use arrow::datatypes::{
+    DataType as ArrowDataType, Field as ArrowField, Schema as ArrowSchema, TimeUnit,
+};
+use arrow::error::ArrowError;
+use arrow::record_batch::RecordBatch;
+use futures::{Async, Poll, Stream};
+
+use DataType;
+use Error;
+use Result;
+use RowBatch;
+use Scan;
+use Schema;
+use Value;
+
+fn arrow_error(error: ArrowError) -> Error {
+    Error::Serialization(format!("failed to build Arrow array: {:?}", error))
+}
+
+impl DataType {
+    /// Returns the Arrow data type corresponding to the Kudu data type.
+    ///
+    /// Timestamps map to Arrow timestamps with microsecond precision, which is the precision of
+    /// Kudu timestamps. Strings map to UTF-8 arrays, and binary values map to lists of bytes.
+    /// Arrow has no decimal type, so decimals map to their unscaled values: `Decimal32` and
+    /// `Decimal64` map to 32 and 64 bit integers, and `Decimal128` maps to lists of 16
+    /// little-endian bytes.
+    pub fn to_arrow(self) -> ArrowDataType {
+        match self {
+            DataType::Bool => ArrowDataType::Boolean,
+            DataType::Int8 => ArrowDataType::Int8,
+            DataType::Int16 => ArrowDataType::Int16,
+            DataType::Int32 => ArrowDataType::Int32,
+            DataType::Int64 => ArrowDataType::Int64,
+            DataType::Timestamp => ArrowDataType::Timestamp(TimeUnit::Microsecond),
+            DataType::Float => ArrowDataType::Float32,
+            DataType::Double => ArrowDataType::Float64,
+            DataType::Binary | DataType::Decimal128 => {
+                ArrowDataType::List(Box::new(ArrowDataType::UInt8))
+            }
+            DataType::String => ArrowDataType::Utf8,
+            DataType::Decimal32 => ArrowDataType::Int32,
+            DataType::Decimal64 => ArrowDataType::Int64,
+        }
+    }
+}
+
+impl Schema {
+    /// Returns the Arrow schema corresponding to the Kudu schema.
+    pub fn to_arrow(&self) -> ArrowSchema {
+        let fields = self
+            .columns()
+            .iter()
+            .map(|column| {
+                ArrowField::new(
+                    column.name(),
+                    column.data_type().to_arrow(),
+                    column.is_nullable(),
+                )
+            }).collect();
+        ArrowSchema::new(fields)
+    }
+}
+
+macro_rules! bytes_array {
+    ($batch:expr, $idx:expr, $ty:ty, |$value:ident| $bytes:expr) => {{
+        let mut builder = ListBuilder::new(UInt8Builder::new($batch.num_rows()));
+        for value in $batch.column::<$ty, _>($idx)? {
+            match value {
+                Some($value) => builder
+                    .values()
+                    .append_slice($bytes)
+                    .and_then(|_| builder.append(true)),
+                None => builder.append(false),
+            }.map_err(arrow_error)?;
+        }
+        Arc::new(builder.finish()) as ArrayRef
+    }};
+}
+
+macro_rules! primitive_array {
+    ($batch:expr, $idx:expr, $ty:ty, $builder:ident) => {{
+        let mut builder = $builder::new($batch.num_rows());
+        for value in $batch.column::<$ty, _>($idx)? {
+            match value {
+                Some(value) => builder.append_value(value),
+                None => builder.append_null(),
+            }.map_err(arrow_error)?;
+        }
+        Arc::new(builder.finish()) as ArrayRef
+    }};
+}
+
+impl RowBatch {
+    /// Converts the batch into an Arrow record batch, with the schema returned by
+    /// `Schema::to_arrow` for the projected schema.
+    pub fn to_record_batch(&self) -> Result<RecordBatch> {
+        let schema = self.projected_schema();
+        let mut columns = Vec::with_capacity(schema.columns().len());
+        for (idx, column) in schema.columns().iter().enumerate() {
+            let array = match column.data_type() {
+                DataType::Bool => primitive_array!(self, idx, bool, BooleanBuilder),
+                DataType::Int8 => primitive_array!(self, idx, i8, Int8Builder),
+                DataType::Int16 => primitive_array!(self, idx, i16, Int16Builder),
+                DataType::Int32 => primitive_array!(self, idx, i32, Int32Builder),
+                DataType::Int64 => primitive_array!(self, idx, i64, Int64Builder),
+                DataType::Timestamp => {
+                    primitive_array!(self, idx, i64, TimestampMicrosecondBuilder)
+                }
+                DataType::Float => primitive_array!(self, idx, f32, Float32Builder),
+                DataType::Double => primitive_array!(self, idx, f64, Float64Builder),
+                DataType::Binary => bytes_array!(self, idx, &[u8], |value| value),
+                DataType::String => {
+                    // Arrow's binary builder produces UTF-8 arrays.
+                    let mut builder = BinaryBuilder::new(self.num_rows());
+                    for value in self.column::<&[u8], _>(idx)? {
+                        match value {
+                            Some(value) => builder
+                                .append_values(value)
+                                .and_then(|_| builder.append(true)),
+                            None => builder.append_null(),
+                        }.map_err(arrow_error)?;
+                    }
+                    Arc::new(builder.finish()) as ArrayRef
+                }
+                DataType::Decimal32 => primitive_array!(self, idx, i32, Int32Builder),
+                DataType::Decimal64 => primitive_array!(self, idx, i64, Int64Builder),
+                DataType::Decimal128 => bytes_array!(self, idx, i128, |value| &value.encode()),
+            };
+            columns.push(array);
+        }
+        Ok(RecordBatch::new(Arc::new(schema.to_arrow()), columns))
+    }
+}
+
+impl Scan {
+    /// Converts the scan into a stream of Arrow record batches.
+    pub fn into_record_batches(self) -> RecordBatches {
+        RecordBatches { scan: self }
+    }
+}
+
+/// A stream of Arrow record batches, converted from the row batches returned by a scan.
+#[must_use = "streams do nothing unless polled"]
+pub struct RecordBatches {
+    scan: Scan,
+}
+
+impl RecordBatches {
+    /// Returns the scan which the record batches are converted from.
+    pub fn scan(&self) -> &Scan {
+        &self.scan
+    }
+}
+
+impl Stream for RecordBatches {
+    type Item = RecordBatch;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Option<RecordBatch>, Error> {
+        match try_ready!(self.scan.poll()) {
+            Some(batch) => Ok(Async::Ready(Some(batch.to_record_batch()?))),
+            None => Ok(Async::Ready(None)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use arrow::array::{Array, BinaryArray, Int32Array, Int64Array, ListArray, UInt8Array};
+    use env_logger;
+    use futures::future;
+    use tokio::runtime::current_thread::Runtime;
+
+    use super::*;
+    use mini_cluster::{create_table, insert_rows, MiniCluster};
+    use Client;
+    use Column;
+    use Options;
+    use SchemaBuilder;
+    use TableBuilder;
+
+    #[test]
+    fn schema() {
+        let schema = SchemaBuilder::new()
+            .add_column(Column::new("key", DataType::Int32).set_not_null())
+            .add_column(Column::new("bool", DataType::Bool).set_nullable())
+            .add_column(Column::new("i8", DataType::Int8).set_nullable())
+            .add_column(Column::new("i16", DataType::Int16).set_nullable())
+            .add_column(Column::new("i64", DataType::Int64).set_nullable())
+            .add_column(Column::new("timestamp", DataType::Timestamp).set_nullable())
+            .add_column(Column::new("f32", DataType::Float).set_nullable())
+            .add_column(Column::new("f64", DataType::Double).set_nullable())
+            .add_column(Column::new("binary", DataType::Binary).set_nullable())
+            .add_column(Column::new("string", DataType::String).set_nullable())
+            .add_column(Column::new("decimal32", DataType::Decimal32).set_nullable())
+            .add_column(Column::new("decimal64", DataType::Decimal64).set_nullable())
+            .add_column(Column::new("decimal128", DataType::Decimal128).set_nullable())
+            .set_primary_key(vec!["key"])
+            .build()
+            .unwrap();
+
+        let bytes = ArrowDataType::List(Box::new(ArrowDataType::UInt8));
+        let expected = vec![
+            ArrowField::new("key", ArrowDataType::Int32, false),
+            ArrowField::new("bool", ArrowDataType::Boolean, true),
+            ArrowField::new("i8", ArrowDataType::Int8, true),
+            ArrowField::new("i16", ArrowDataType::Int16, true),
+            ArrowField::new("i64", ArrowDataType::Int64, true),
+            ArrowField::new(
+                "timestamp",
+                ArrowDataType::Timestamp(TimeUnit::Microsecond),
+                true,
+            ),
+            ArrowField::new("f32", ArrowDataType::Float32, true),
+            ArrowField::new("f64", ArrowDataType::Float64, true),
+            ArrowField::new("binary", bytes.clone(), true),
+            ArrowField::new("string", ArrowDataType::Utf8, true),
+            ArrowField::new("decimal32", ArrowDataType::Int32, true),
+            ArrowField::new("decimal64", ArrowDataType::Int64, true),
+            ArrowField::new("decimal128", bytes, true),
+        ];
+        assert_eq!(ArrowSchema::new(expected), schema.to_arrow());
+    }
+
+    /// Returns the bytes of a row of a list of bytes array.
+    fn list_value(array: &ListArray, row: usize) -> Vec<u8> {
+        let values = array.values();
+        let values = values.as_any().downcast_ref::<UInt8Array>().unwrap();
+        let offset = array.value_offset(row) as usize;
+        let len = array.value_length(row) as usize;
+        (offset..offset + len).map(|idx| values.value(idx)).collect()
+    }
+
+    #[test]
+    fn record_batches() {
+        let _ = env_logger::try_init();
+        let mut cluster = MiniCluster::default();
+        let mut runtime = Runtime::new().unwrap();
+
+        let mut client = runtime
+            .block_on(Client::new(cluster.master_addrs(), Options::default()))
+            .expect("client");
+
+        let schema = SchemaBuilder::new()
+            .add_column(Column::new("key", DataType::Int32).set_not_null())
+            .add_column(Column::new("val", DataType::Int64).set_nullable())
+            .add_column(Column::new("name", DataType::String).set_nullable())
+            .add_column(Column::new("data", DataType::Binary).set_nullable())
+            .add_column(Column::new("dec", DataType::Decimal128).set_nullable())
+            .set_primary_key(vec!["key"])
+            .build()
+            .unwrap();
+
+        let mut table_builder = TableBuilder::new("record_batches", schema.clone());
+        table_builder.set_range_partition_columns(vec!["key"]);
+        table_builder.set_num_replicas(1);
+        let table = create_table(&mut runtime, &mut client, table_builder);
+
+        let num_rows = 100i32;
+        insert_rows(&mut runtime, &table, num_rows, |row, i| {
+            row.set("key", i).unwrap();
+            if i % 2 == 0 {
+                row.set("val", i64::from(i)).unwrap();
+                row.set("name", i.to_string()).unwrap();
+                row.set("data", i.to_string().into_bytes()).unwrap();
+                row.set("dec", -i128::from(i)).unwrap();
+            }
+        });
+
+        let scan = runtime
+            .block_on(future::lazy::<_, Result<Scan>>(|| {
+                Ok(table.scan_builder().ordered().build())
+            })).unwrap();
+        let batches: Vec<RecordBatch> = runtime
+            .block_on(future::lazy(|| scan.into_record_batches().collect()))
+            .unwrap();
+
+        let mut i = 0;
+        for batch in batches {
+            assert_eq!(&table.schema().to_arrow(), &*batch.schema());
+            for (column, field) in batch.columns().iter().zip(batch.schema().fields()) {
+                assert_eq!(field.data_type(), column.data_type());
+            }
+            let keys = batch
+                .column(0)
+                .as_any()
+                .downcast_ref::<Int32Array>()
+                .unwrap();
+            let vals = batch
+                .column(1)
+                .as_any()
+                .downcast_ref::<Int64Array>()
+                .unwrap();
+            let names = batch
+                .column(2)
+                .as_any()
+                .downcast_ref::<BinaryArray>()
+                .unwrap();
+            let data = batch
+                .column(3)
+                .as_any()
+                .downcast_ref::<ListArray>()
+                .unwrap();
+            let decs = batch
+                .column(4)
+                .as_any()
+                .downcast_ref::<ListArray>()
+                .unwrap();
+            for row in 0..batch.num_rows() {
+                assert_eq!(i, keys.value(row));
+                assert_eq!(i % 2 != 0, vals.is_null(row));
+                assert_eq!(i % 2 != 0, names.is_null(row));
+                assert_eq!(i % 2 != 0, data.is_null(row));
+                assert_eq!(i % 2 != 0, decs.is_null(row));
+                if i % 2 == 0 {
+                    assert_eq!(i64::from(i), vals.value(row));
+                    assert_eq!(i.to_string().as_bytes(), names.value(row));
+                    assert_eq!(i.to_string().into_bytes(), list_value(data, row));
+                    assert_eq!((-i128::from(i)).encode(), list_value(decs, row));
+                }
+                i += 1;
+            }
+        }
+        assert_eq!(num_rows, i);
+    }
+}
@@ -16,6 +16,7 @@ use futures::stream::FuturesUnordered;
 use futures::{Async, Future, Poll, Stream};
 use krpc::{Call, Proxy, RpcFuture};
 use prost::Message;
+use rand::{self, Rng};
 use tokio_timer::Delay;
 
 use backoff::Backoff;
@@ -27,17 +28,35 @@ use MasterErrorCode;
 use TabletServerError;
 use TabletServerErrorCode;
 
-/// The strategy for speculative execution.
+/// The strategy for speculative execution, also known as hedging.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Speculation {
+    /// Sends the RPC to one replica at a time. The next replica is attempted only if the RPC
+    /// fails.
+    None,
+    /// Sends the RPC to every replica at once.
     Full,
+    /// Sends the RPC to the next replica each time the duration elapses without a response.
     Staggered(Duration),
 }
 
 /// The policy for replica selection.
-#[derive(PartialEq)]
-pub(crate) enum Selection {
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Selection {
+    /// Prefers the leader replica. The other replicas are attempted if the leader is not known or
+    /// fails, or when the speculative execution strategy hedges the RPC.
     Leader,
+    /// Prefers replicas hosted on the local machine, followed by replicas with the lowest
+    /// observed latency. Replicas without latency observations are preferred over replicas with
+    /// observations, so that the latency of every replica is eventually observed.
+    ///
+    /// The observed latency is the round trip time of the RPCs sent to the replica, including the
+    /// time spent by the tablet server to process them, such as opening a scanner and reading its
+    /// first batch of rows. It measures how responsive the replica is, not only the network
+    /// latency to its tablet server.
     Closest,
+    /// Attempts the replicas in random order, which spreads load evenly across the replicas.
+    Any,
 }
 
 impl Selection {
@@ -57,14 +76,27 @@ impl Selection {
                     queue.push_back(ReplicaState::new(idx, backoff.clone(), replica.proxy()));
                 }
             },
-            // TODO: Figure out how to find who's closest.
-            Selection::Closest => for (idx, replica) in replicas.iter().enumerate() {
-                if replica.is_stale() {
-                    continue;
-                } else {
-                    queue.push_back(ReplicaState::new(idx, backoff.clone(), replica.proxy()));
+            Selection::Closest => {
+                let mut idxs = (0..replicas.len())
+                    .filter(|&idx| !replicas[idx].is_stale())
+                    .collect::<Vec<_>>();
+                idxs.sort_by_key(|&idx| {
+                    let replica = &replicas[idx];
+                    (!replica.is_local(), replica.latency().unwrap_or_default())
+                });
+                for idx in idxs {
+                    queue.push_back(ReplicaState::new(idx, backoff.clone(), replicas[idx].proxy()));
                 }
-            },
+            }
+            Selection::Any => {
+                let mut idxs = (0..replicas.len())
+                    .filter(|&idx| !replicas[idx].is_stale())
+                    .collect::<Vec<_>>();
+                rand::thread_rng().shuffle(&mut idxs);
+                for idx in idxs {
+                    queue.push_back(ReplicaState::new(idx, backoff.clone(), replicas[idx].proxy()));
+                }
+            }
         }
         queue
     }
@@ -88,6 +120,20 @@ pub(crate) trait Replica {
 
     /// Marks the replica location as stale.
     fn mark_stale(&self);
+
+    /// Returns true if the replica is hosted on the local machine.
+    fn is_local(&self) -> bool {
+        false
+    }
+
+    /// Returns the average observed RPC latency of the replica, if it has been observed.
+    fn latency(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Records the observed latency of an RPC to the replica, including the time spent by the
+    /// tablet server to process the RPC.
+    fn observe_latency(&self, _latency: Duration) {}
 }
 
 pub(crate) trait ReplicaSet {
@@ -102,6 +148,8 @@ struct ReplicaState {
     proxy: Proxy,
     backoff: Backoff,
     failure: Option<Error>,
+    /// The time the latest RPC was sent to the replica.
+    sent: Instant,
 }
 
 impl ReplicaState {
@@ -111,8 +159,23 @@ impl ReplicaState {
             backoff,
             proxy,
             failure: None,
+            sent: Instant::now(),
         }
     }
+
+    /// Sends the RPC to the replica.
+    fn send<Req, Resp>(
+        mut self,
+        call: Call<Req, Resp>,
+    ) -> ContextFuture<RpcFuture<Resp>, ReplicaState>
+    where
+        Req: Message + 'static,
+        Resp: Message + Default,
+    {
+        self.sent = Instant::now();
+        let rpc = self.proxy.send(call);
+        ContextFuture::new(rpc, self)
+    }
 }
 
 impl fmt::Debug for ReplicaState {
@@ -151,6 +214,9 @@ where
 
     /// Holds replicas which have failed with non-retriable errors.
     failures: Vec<ReplicaState>,
+
+    /// The index of the replica which responded successfully.
+    responder: Option<usize>,
 }
 
 impl<Set, Req, Resp> ReplicaRpc<Set, Req, Resp>
@@ -177,9 +243,15 @@ where
             backoff: FuturesUnordered::new(),
             speculative_timer: None,
             failures: Vec::new(),
+            responder: None,
         }
     }
 
+    /// Returns the index of the replica which responded successfully, if the RPC has completed.
+    pub(crate) fn responder(&self) -> Option<usize> {
+        self.responder
+    }
+
     fn speculation_timer_is_ready(&mut self) -> bool {
         self.speculative_timer
             .as_mut()
@@ -200,28 +272,29 @@ where
         let in_flight_count = self.in_flight.len();
         if !self.queue.is_empty() {
             match self.speculation {
-                Speculation::Full => for mut replica in self.queue.drain(..) {
+                Speculation::None => {
+                    // Issue an RPC only if no other replica has an RPC outstanding.
+                    if self.in_flight.is_empty() && self.backoff.is_empty() {
+                        let replica = self.queue.pop_front().unwrap();
+                        self.in_flight.push(replica.send(self.call.clone()));
+                    }
+                }
+                Speculation::Full => for replica in self.queue.drain(..) {
                     // Completely drain the queue and issue RPCs against all replicas.
-                    let rpc = replica.proxy.send(self.call.clone());
-                    let context = ContextFuture::new(rpc, replica);
-                    self.in_flight.push(context);
+                    self.in_flight.push(replica.send(self.call.clone()));
                 },
                 Speculation::Staggered(duration) => {
                     if self.speculation_timer_is_ready() {
-                        let mut replica = self.queue.pop_front().unwrap();
-                        let rpc = replica.proxy.send(self.call.clone());
-                        let context = ContextFuture::new(rpc, replica);
-                        self.in_flight.push(context);
+                        let replica = self.queue.pop_front().unwrap();
+                        self.in_flight.push(replica.send(self.call.clone()));
                         self.reset_speculation_timer(duration);
                     }
                 }
             }
         }
 
-        while let Async::Ready(Some((_, mut replica))) = self.backoff.poll().unwrap() {
-            let rpc = replica.proxy.send(self.call.clone());
-            let context = ContextFuture::new(rpc, replica);
-            self.in_flight.push(context);
+        while let Async::Ready(Some((_, replica))) = self.backoff.poll().unwrap() {
+            self.in_flight.push(replica.send(self.call.clone()));
         }
         in_flight_count != self.in_flight.len()
     }
@@ -239,9 +312,12 @@ where
 
             match response {
                 Ok((response, sidecars)) => {
+                    self.replica_set.replicas()[replica.index]
+                        .observe_latency(replica.sent.elapsed());
                     if self.selection == Selection::Leader {
                         self.replica_set.replicas()[replica.index].mark_leader();
                     }
+                    self.responder = Some(replica.index);
                     return Ok(Async::Ready((replica.proxy, response, sidecars)));
                 }
 
@@ -269,7 +345,7 @@ where
                             // TODO: implement the higher level retry mechanism mentioned above.
                             return Err(error);
                         }
-                        Selection::Closest => {
+                        Selection::Closest | Selection::Any => {
                             // If we aren't relying on finding the leader then we can continue
                             // trying the RPC at other replicas.
                             replica.failure = Some(error);
@@ -386,23 +462,19 @@ impl Replica for Proxy {
         self.clone()
     }
 
+    // A lone proxy has no leadership or location state to track.
+
     fn is_leader(&self) -> bool {
-        unimplemented!()
+        false
     }
 
-    fn mark_leader(&self) {
-        unimplemented!()
-    }
+    fn mark_leader(&self) {}
 
-    fn mark_follower(&self) {
-        unimplemented!()
-    }
+    fn mark_follower(&self) {}
 
     fn is_stale(&self) -> bool {
-        unimplemented!()
+        false
     }
 
-    fn mark_stale(&self) {
-        unimplemented!()
-    }
+    fn mark_stale(&self) {}
 }
@@ -0,0 +1,151 @@
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+use krpc::RequestId;
+use parking_lot::Mutex;
+use rand;
+
+/// Assigns request IDs to RPCs which must be applied at most once, such as writes.
+///
+/// Each RPC is assigned a sequence number, which is incomplete until the RPC completes or is
+/// abandoned. Tablet servers cache the response of each sequence number, so that retries of an
+/// RPC are answered from the cache instead of being applied again. Request IDs carry the first
+/// incomplete sequence number of the client, which allows the servers to discard the cached
+/// responses of earlier sequence numbers.
+#[derive(Clone)]
+pub(crate) struct RequestTracker {
+    client_id: Arc<str>,
+    sequence: Arc<Mutex<Sequence>>,
+}
+
+struct Sequence {
+    next_seq_no: i64,
+    incomplete: BTreeSet<i64>,
+}
+
+impl Sequence {
+    fn first_incomplete_seq_no(&self) -> i64 {
+        self.incomplete
+            .iter()
+            .next()
+            .cloned()
+            .unwrap_or(self.next_seq_no)
+    }
+}
+
+impl RequestTracker {
+    /// Creates a new request tracker with a random client ID.
+    pub(crate) fn new() -> RequestTracker {
+        let client_id = format!("{:016x}{:016x}", rand::random::<u64>(), rand::random::<u64>());
+        RequestTracker {
+            client_id: client_id.into(),
+            sequence: Arc::new(Mutex::new(Sequence {
+                next_seq_no: 0,
+                incomplete: BTreeSet::new(),
+            })),
+        }
+    }
+
+    /// Returns a request with a new sequence number. The sequence number is complete once the
+    /// request is dropped.
+    pub(crate) fn new_request(&self) -> Request {
+        let mut sequence = self.sequence.lock();
+        let seq_no = sequence.next_seq_no;
+        sequence.next_seq_no += 1;
+        sequence.incomplete.insert(seq_no);
+        Request {
+            id: RequestId {
+                client_id: self.client_id.to_string(),
+                seq_no,
+                first_incomplete_seq_no: sequence.first_incomplete_seq_no(),
+                attempt_no: 0,
+            },
+            tracker: self.clone(),
+        }
+    }
+}
+
+/// An RPC which must be applied at most once.
+///
+/// The sequence number of the request is incomplete until the request is dropped, which happens
+/// when the RPC completes or is abandoned.
+pub(crate) struct Request {
+    id: RequestId,
+    tracker: RequestTracker,
+}
+
+impl Request {
+    /// Returns the request ID of the current attempt of the RPC.
+    pub(crate) fn id(&self) -> &RequestId {
+        &self.id
+    }
+
+    /// Returns the request ID for the next attempt of the RPC.
+    pub(crate) fn retry(&mut self) -> &RequestId {
+        self.id.first_incomplete_seq_no = self.tracker.sequence.lock().first_incomplete_seq_no();
+        self.id.attempt_no += 1;
+        &self.id
+    }
+}
+
+impl Drop for Request {
+    fn drop(&mut self) {
+        self.tracker
+            .sequence
+            .lock()
+            .incomplete
+            .remove(&self.id.seq_no);
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use futures::{future, Future};
+
+    use super::*;
+
+    #[test]
+    fn sequence_numbers() {
+        let tracker = RequestTracker::new();
+        assert_eq!(32, tracker.client_id.len());
+
+        let a = tracker.new_request();
+        let b = tracker.new_request();
+        let mut c = tracker.new_request();
+        assert_eq!(0, a.id().seq_no);
+        assert_eq!(1, b.id().seq_no);
+        assert_eq!(2, c.id().seq_no);
+        assert_eq!(0, c.id().first_incomplete_seq_no);
+
+        drop(b);
+        let d = tracker.new_request();
+        assert_eq!(0, d.id().first_incomplete_seq_no);
+
+        drop(a);
+        let retry = c.retry().clone();
+        assert_eq!(2, retry.seq_no);
+        assert_eq!(1, retry.attempt_no);
+        assert_eq!(2, retry.first_incomplete_seq_no);
+
+        drop(c);
+        drop(d);
+        assert_eq!(4, tracker.new_request().id().first_incomplete_seq_no);
+    }
+
+    #[test]
+    fn abandoned_request() {
+        let tracker = RequestTracker::new();
+        let request = tracker.new_request();
+        assert_eq!(0, tracker.sequence.lock().first_incomplete_seq_no());
+
+        // A pending RPC which owns the request.
+        let rpc = future::empty::<(), ()>().map(move |_| drop(request));
+        assert_eq!(0, tracker.sequence.lock().first_incomplete_seq_no());
+
+        // Abandoning the RPC completes the sequence number.
+        drop(rpc);
+        assert_eq!(1, tracker.sequence.lock().first_incomplete_seq_no());
+        assert_eq!(1, tracker.new_request().id().first_incomplete_seq_no);
+    }
+}
@@ -186,3 +186,4 @@ macro_rules! tserver_response {
 
 tserver_response!(WriteResponsePb);
 tserver_response!(ScanResponsePb);
+tserver_response!(ScannerKeepAliveResponsePb);
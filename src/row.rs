@@ -4,7 +4,9 @@ use std::marker::PhantomData;
 use std::mem;
 use std::slice;
 use std::str;
+use std::sync::Arc;
 
+use scanner::RowBatchData;
 use util;
 use value::{read_var_len_value, write_var_len_value, Value};
 use ColumnSelector;
@@ -33,6 +35,10 @@ pub struct Row<'data> {
     /// The schema of the row.
     schema: Schema,
 
+    /// The buffers holding the row data and the values referenced by the row, if the row is a
+    /// contiguous row which shares the buffers of a row batch.
+    owner: Option<Arc<RowBatchData>>,
+
     _marker: PhantomData<&'data [u8]>,
 }
 
@@ -52,6 +58,7 @@ impl<'data> Row<'data> {
         Row {
             tagged_ptr: (ptr << 1) | 1,
             schema,
+            owner: None,
             _marker: PhantomData::default(),
         }
     }
@@ -62,6 +69,19 @@ impl<'data> Row<'data> {
         Row {
             tagged_ptr,
             schema,
+            owner: None,
+            _marker: PhantomData::default(),
+        }
+    }
+
+    /// Creates a constant contiguous row referencing the provided data, which must be held by
+    /// the row batch buffers.
+    pub(crate) fn shared(schema: Schema, data: &[u8], owner: Arc<RowBatchData>) -> Row<'static> {
+        let tagged_ptr = (data.as_ptr() as i64) << 1;
+        Row {
+            tagged_ptr,
+            schema,
+            owner: Some(owner),
             _marker: PhantomData::default(),
         }
     }
@@ -75,7 +95,7 @@ impl<'data> Row<'data> {
         V: Value<'data>,
     {
         let idx = column.column_index(&self.schema)?;
-        self.check_column_for_write::<V>(idx)?;
+        self.check_column_for_write(idx, &value)?;
         unsafe { Ok(self.set_unchecked(idx, value)) }
     }
 
@@ -282,10 +302,31 @@ impl<'data> Row<'data> {
         (self.tagged_ptr >> 1) as _
     }
 
+    /// Converts a constant contiguous row into a mutable partial row with every column set.
+    ///
+    /// String and binary values remain borrowed from the contiguous row's indirect data.
     #[inline(never)]
     fn into_partial_row(&mut self) {
-        debug_assert!(self.is_partial_row());
-        unimplemented!()
+        debug_assert!(self.is_contiguous_row());
+        let data_len = partial_row_data_len(&self.schema);
+        let is_set_offset = self.schema.row_len()
+            + self.schema.has_nullable_columns() as usize * self.schema.bitmap_len();
+
+        // N.B. zeroing the is-set bitmap portion of the data array is necessary.
+        let mut data = vec![0u8; data_len];
+        unsafe {
+            let contiguous_data = slice::from_raw_parts(self.data(), is_set_offset);
+            data[..is_set_offset].copy_from_slice(contiguous_data);
+            for idx in 0..self.schema.columns().len() {
+                bitmap_set(data.as_mut_ptr().offset(is_set_offset as isize), idx);
+            }
+        }
+        debug_assert_eq!(data.len(), data_len);
+        debug_assert_eq!(data.capacity(), data_len);
+        let ptr = data.as_ptr() as i64;
+        mem::forget(data);
+
+        self.tagged_ptr = (ptr << 1) | 1;
     }
 
     /// Copies all borrowed values into a new row with a `'static` lifetime.
@@ -315,6 +356,7 @@ impl<'data> Row<'data> {
                     mem::forget(copy);
                 }
             }
+            self.owner = None;
 
             // Extend the lifetime, which is safe now that there are no borrowed values.
             mem::transmute(self)
@@ -348,8 +390,9 @@ impl<'data> Row<'data> {
         }
     }
 
-    /// Checks that the column with the specified index has the expected type.
-    fn check_column_for_write<V>(&self, idx: usize) -> Result<()>
+    /// Checks that the column with the specified index has the expected type, and that a decimal
+    /// value is within the precision of the column.
+    fn check_column_for_write<V>(&self, idx: usize, value: &V) -> Result<()>
     where
         V: Value<'data>,
     {
@@ -368,6 +411,16 @@ impl<'data> Row<'data> {
                 column
             )));
         }
+        if let (Some(precision), Some(unscaled)) = (column.precision(), value.to_unscaled()) {
+            let max = util::decimal_max(precision);
+            if unscaled < -max || unscaled > max {
+                return Err(Error::InvalidArgument(format!(
+                    "value {} exceeds the precision of column {:?}",
+                    util::decimal_to_string(unscaled, column.scale().unwrap()),
+                    column
+                )));
+            }
+        }
         Ok(())
     }
 
@@ -414,6 +467,7 @@ impl<'data> Clone for Row<'data> {
             Row {
                 tagged_ptr: self.tagged_ptr,
                 schema: self.schema.clone(),
+                owner: self.owner.clone(),
                 _marker: PhantomData::default(),
             }
         } else {
@@ -452,6 +506,7 @@ impl<'data> Clone for Row<'data> {
                 Row {
                     tagged_ptr: ((ptr as i64) << 1) | 1,
                     schema: self.schema.clone(),
+                    owner: self.owner.clone(),
                     _marker: PhantomData::default(),
                 }
             }
@@ -633,6 +688,9 @@ mod tests {
 
     use super::*;
     use schema;
+    use Column;
+    use DataType;
+    use SchemaBuilder;
 
     #[test]
     fn test_get_set() {
@@ -838,4 +896,26 @@ mod tests {
             &format!("{:?}", row.clone())
         );
     }
+
+    #[test]
+    fn test_decimal_precision() {
+        let schema = SchemaBuilder::new()
+            .add_column(Column::new("key", DataType::Int32).set_not_null())
+            .add_column(
+                Column::new("d", DataType::Decimal64)
+                    .set_precision(12)
+                    .set_scale(2)
+                    .set_nullable(),
+            ).set_primary_key(vec!["key"])
+            .build()
+            .unwrap();
+        let mut row = schema.new_row();
+
+        row.set("d", 999_999_999_999i64).unwrap();
+        row.set("d", -999_999_999_999i64).unwrap();
+        row.set("d", None::<i64>).unwrap();
+        assert!(row.set("d", 1_000_000_000_000i64).is_err());
+        assert!(row.set("d", Some(-1_000_000_000_000i64)).is_err());
+        assert_eq!(None, row.get::<_, Option<i64>>("d").unwrap());
+    }
 }
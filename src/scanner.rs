@@ -1,36 +1,113 @@
+use std::cmp::{self, Reverse};
+use std::collections::{BinaryHeap, HashSet, VecDeque};
 use std::fmt;
 use std::iter::{FusedIterator, IntoIterator};
+use std::marker::PhantomData;
 use std::mem;
+use std::ops::Range;
+use std::slice;
 use std::sync::Arc;
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime};
 
 use bytes::{Bytes, BytesMut};
+use futures::future;
+use futures::sync::oneshot;
 use futures::{Async, Future, Poll, Stream};
 use krpc::Proxy;
+use parking_lot::Mutex;
+use tokio::executor::{DefaultExecutor, Executor};
+use tokio_timer::Delay;
 use vec_map::{self, VecMap};
 
 use backoff::Backoff;
+use key;
 use meta_cache::{Entry, Lookup, TableLocations};
-use pb::tserver::{NewScanRequestPb, ScanRequestPb, ScanResponsePb, TabletServerService};
-use pb::{ColumnPredicatePb, ColumnSchemaPb, ExpectField, RowwiseRowBlockPb};
-use replica::{ReplicaRpc, Selection, Speculation};
+use partition_pruner::PartitionPruner;
+use pb::client::ScanTokenPb;
+use pb::tserver::{
+    NewScanRequestPb, ScanRequestPb, ScanResponsePb, ScannerKeepAliveRequestPb,
+    ScannerKeepAliveResponsePb, TabletServerService,
+};
+use pb::{
+    ColumnPredicatePb, ColumnSchemaPb, ExpectField, OrderMode as OrderModePb,
+    ReadMode as ReadModePb, RowwiseRowBlockPb,
+};
+use prost::Message;
+use replica::{Replica, ReplicaRpc, Selection, Speculation};
+use table::Table;
 use tablet::Tablet;
+use util;
+use value::{read_var_len_value, Value};
+use Client;
 use Column;
 use ColumnSelector;
+use DataType;
 use Error;
 use Filter;
+use Partition;
+use RangePartitionBound;
 use Result;
 use Row;
 use ScannerId;
 use Schema;
 use TabletId;
+use TabletServerErrorCode;
+
+/// The read mode of a scan, which determines the consistency of the returned rows.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ReadMode {
+    /// Reads the latest committed data on each tablet replica. Each tablet is read at a different
+    /// point in time, so the results are not repeatable. This is the default read mode.
+    Latest,
+
+    /// Reads a consistent snapshot of the table at the provided hybrid time. If no timestamp is
+    /// provided, the tablet server handling the first tablet chooses one, and the remaining
+    /// tablets are read at the same timestamp.
+    Snapshot(Option<u64>),
+
+    /// Reads a snapshot which includes every write observed by the client, as tracked by
+    /// `Client::latest_observed_timestamp`. The remaining tablets are read at the snapshot
+    /// timestamp chosen for the first tablet.
+    ReadYourWrites,
+}
+
+impl ReadMode {
+    /// Returns a snapshot read mode at the provided wall clock time.
+    pub fn snapshot_at(time: SystemTime) -> ReadMode {
+        // Hybrid times hold the physical time in microseconds in the upper 52 bits.
+        ReadMode::Snapshot(Some((util::time_to_us(time) as u64) << 12))
+    }
+}
+
+impl Default for ReadMode {
+    fn default() -> ReadMode {
+        ReadMode::Latest
+    }
+}
 
 #[derive(Clone)]
 pub struct ScanBuilder {
-    table_schema: Schema,
-    table_locations: TableLocations,
+    table: Table,
     projected_columns: Vec<usize>,
     filters: VecMap<Filter>,
+    read_mode: ReadMode,
+    fault_tolerant: bool,
+    ordered: bool,
+    parallelism: usize,
+    prefetch: usize,
+    lower_bound: Option<RangePartitionBound>,
+    upper_bound: Option<RangePartitionBound>,
+    lower_bound_primary_key: Vec<u8>,
+    upper_bound_primary_key: Vec<u8>,
+    lower_bound_partition_key: Vec<u8>,
+    upper_bound_partition_key: Vec<u8>,
+    tablet_ids: Option<HashSet<TabletId>>,
+    selection: Selection,
+    speculation: Speculation,
+    limit: Option<u64>,
+    batch_size_bytes: Option<u32>,
+    timeout: Duration,
+    keep_alive_interval: Duration,
 }
 
 fn column_to_pb(column: &Column) -> ColumnSchemaPb {
@@ -43,15 +120,87 @@ fn column_to_pb(column: &Column) -> ColumnSchemaPb {
 }
 
 impl ScanBuilder {
-    pub(crate) fn new(table_schema: Schema, table_locations: TableLocations) -> ScanBuilder {
-        let num_columns = table_schema.columns().len();
+    pub(crate) fn new(table: Table) -> ScanBuilder {
+        let num_columns = table.schema().columns().len();
         let projected_columns = (0..num_columns).collect::<Vec<_>>();
         ScanBuilder {
-            table_schema,
-            table_locations,
+            table,
             projected_columns,
             filters: VecMap::new(),
+            read_mode: ReadMode::default(),
+            fault_tolerant: false,
+            ordered: false,
+            parallelism: 1,
+            prefetch: 0,
+            lower_bound: None,
+            upper_bound: None,
+            lower_bound_primary_key: Vec::new(),
+            upper_bound_primary_key: Vec::new(),
+            lower_bound_partition_key: Vec::new(),
+            upper_bound_partition_key: Vec::new(),
+            tablet_ids: None,
+            selection: Selection::Closest,
+            speculation: Speculation::Staggered(Duration::from_millis(100)),
+            limit: None,
+            batch_size_bytes: None,
+            timeout: Duration::from_secs(60),
+            keep_alive_interval: Duration::from_secs(15),
+        }
+    }
+
+    /// Creates a scan builder from a scan token.
+    fn from_token_pb(table: Table, pb: ScanTokenPb) -> Result<ScanBuilder> {
+        if !pb.feature_flags.is_empty() {
+            return Err(Error::InvalidArgument(
+                "scan token requires unsupported features".to_owned(),
+            ));
+        }
+
+        let schema = table.schema().clone();
+        let mut builder = ScanBuilder::new(table);
+
+        builder.projected_columns.clear();
+        for column in pb.projected_columns {
+            let idx = (&column.name[..]).column_index(&schema)?;
+            if schema.columns()[idx].data_type().to_pb() != column.type_ {
+                return Err(Error::InvalidArgument(format!(
+                    "scan token column {} type does not match the table schema",
+                    column.name
+                )));
+            }
+            builder.projected_columns.push(idx);
+        }
+
+        for predicate in pb.column_predicates {
+            let idx = {
+                let name = predicate
+                    .column
+                    .as_ref()
+                    .expect_field("ColumnPredicatePB", "column")?;
+                (&name[..]).column_index(&schema)?
+            };
+            let filter = Filter::from_pb(predicate, &schema.columns()[idx])?;
+            builder = builder.filter(idx, filter)?;
+        }
+
+        builder.lower_bound_primary_key = pb.lower_bound_primary_key.unwrap_or_default();
+        builder.upper_bound_primary_key = pb.upper_bound_primary_key.unwrap_or_default();
+        builder.lower_bound_partition_key = pb.lower_bound_partition_key.unwrap_or_default();
+        builder.upper_bound_partition_key = pb.upper_bound_partition_key.unwrap_or_default();
+
+        builder.read_mode = match pb.read_mode.and_then(ReadModePb::from_i32) {
+            Some(ReadModePb::ReadAtSnapshot) => ReadMode::Snapshot(pb.snap_timestamp),
+            Some(ReadModePb::ReadYourWrites) => ReadMode::ReadYourWrites,
+            _ => ReadMode::Latest,
+        };
+        builder.fault_tolerant = pb.fault_tolerant.unwrap_or(false);
+        builder.limit = pb.limit;
+
+        if let Some(timestamp) = pb.propagated_timestamp {
+            builder.table.client().observe_timestamp(timestamp);
         }
+
+        Ok(builder)
     }
 
     pub fn select<I, C>(mut self, projected_columns: I) -> Result<ScanBuilder>
@@ -62,7 +211,7 @@ impl ScanBuilder {
         self.projected_columns.clear();
         for column_selector in projected_columns {
             self.projected_columns
-                .push(column_selector.column_index(&self.table_schema)?);
+                .push(column_selector.column_index(self.table.schema())?);
         }
         Ok(self)
     }
@@ -79,9 +228,10 @@ impl ScanBuilder {
     where
         C: ColumnSelector,
     {
-        let idx = column.column_index(&self.table_schema)?;
-        let column = &self.table_schema.columns()[idx];
+        let idx = column.column_index(self.table.schema())?;
+        let column = &self.table.schema().columns()[idx];
         filter.check_type(column)?;
+        let filter = filter.clamp(column);
 
         match self.filters.entry(idx) {
             vec_map::Entry::Occupied(mut occupied) => {
@@ -96,13 +246,212 @@ impl ScanBuilder {
         Ok(self)
     }
 
+    /// Restricts the scan to the rows of a partition, such as the partition of a tablet returned
+    /// by `Table::tablets`.
+    pub fn partition(mut self, partition: &Partition) -> ScanBuilder {
+        if partition.lower_bound() > &self.lower_bound_partition_key[..] {
+            self.lower_bound_partition_key = partition.lower_bound().to_owned();
+        }
+        if !partition.upper_bound().is_empty()
+            && (self.upper_bound_partition_key.is_empty()
+                || partition.upper_bound() < &self.upper_bound_partition_key[..])
+        {
+            self.upper_bound_partition_key = partition.upper_bound().to_owned();
+        }
+        self
+    }
+
+    /// Restricts the scan to the tablets with the provided IDs.
+    ///
+    /// Tablets which do not belong to the table, or which have been dropped, are not scanned.
+    pub fn tablets<I>(mut self, tablet_ids: I) -> ScanBuilder
+    where
+        I: IntoIterator<Item = TabletId>,
+    {
+        self.tablet_ids = Some(tablet_ids.into_iter().collect());
+        self
+    }
+
+    /// Sets the policy for selecting the replica of each tablet to scan.
+    ///
+    /// Defaults to `Selection::Closest`.
+    pub fn replica_selection(mut self, selection: Selection) -> ScanBuilder {
+        self.selection = selection;
+        self
+    }
+
+    /// Sets the strategy for opening the scan of each tablet at multiple replicas.
+    ///
+    /// The first replica to respond scans the tablet, and the scanners opened at other replicas
+    /// expire. Defaults to `Speculation::Staggered` with a 100 millisecond delay.
+    pub fn speculation(mut self, speculation: Speculation) -> ScanBuilder {
+        self.speculation = speculation;
+        self
+    }
+
+    /// Sets the read mode of the scan.
+    ///
+    /// Defaults to `ReadMode::Latest`.
+    pub fn read_mode(mut self, read_mode: ReadMode) -> ScanBuilder {
+        self.read_mode = read_mode;
+        self
+    }
+
+    /// Makes the scan fault tolerant.
+    ///
+    /// Fault tolerant scans return the rows of each tablet in primary key order, and keep track of
+    /// the last primary key returned. If the tablet server holding the scanner fails, the tablet
+    /// scan is resumed after that key on another replica. Fault tolerant scans must be snapshot
+    /// scans, so the `ReadMode::Latest` read mode is replaced with `ReadMode::Snapshot(None)`.
+    pub fn fault_tolerant(mut self) -> ScanBuilder {
+        self.fault_tolerant = true;
+        self
+    }
+
+    /// Makes the scan return rows in primary key order.
+    ///
+    /// Ordered scans open every tablet in parallel, and merge the rows of the tablets by primary
+    /// key. Primary key columns which are not selected are appended to the projection. Ordered
+    /// scans must be snapshot scans, so the `ReadMode::Latest` read mode is replaced with
+    /// `ReadMode::Snapshot(None)`.
+    pub fn ordered(mut self) -> ScanBuilder {
+        self.ordered = true;
+        self
+    }
+
+    /// Scans up to `max_tablets` tablets concurrently.
+    ///
+    /// Parallel scans return batches in the order they arrive from the tablet servers, so the
+    /// batches of different tablets are interleaved. Unless a snapshot timestamp is provided,
+    /// snapshot and read-your-writes scans scan the first tablet alone until its snapshot
+    /// timestamp is chosen. Ordered scans always scan every tablet concurrently. Defaults to 1.
+    pub fn parallel(mut self, max_tablets: usize) -> ScanBuilder {
+        self.parallelism = cmp::max(max_tablets, 1);
+        self
+    }
+
+    /// Sets the number of batches fetched ahead of the consumer for each tablet.
+    ///
+    /// Prefetching hides the latency of scan requests from consumers which process batches
+    /// slower than they arrive, at the cost of buffering the batches in memory. Defaults to 0.
+    pub fn prefetch(mut self, batches: usize) -> ScanBuilder {
+        self.prefetch = batches;
+        self
+    }
+
+    /// Limits the number of rows returned by the scan.
+    ///
+    /// The limit is pushed to the tablet servers, and enforced across tablets by the client.
+    pub fn limit(mut self, limit: u64) -> ScanBuilder {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Sets the maximum number of bytes of row data returned by each scan request.
+    ///
+    /// Defaults to the tablet server's default batch size, 1MiB.
+    pub fn batch_size_bytes(mut self, batch_size_bytes: u32) -> ScanBuilder {
+        self.batch_size_bytes = Some(batch_size_bytes);
+        self
+    }
+
+    /// Sets the timeout of each scan request.
+    ///
+    /// Defaults to 60 seconds.
+    pub fn timeout(mut self, timeout: Duration) -> ScanBuilder {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets the interval at which scanners are kept alive while the scan is not being polled.
+    ///
+    /// Tablet servers expire scanners which are idle for longer than the scanner TTL, 60 seconds
+    /// by default. Defaults to 15 seconds.
+    pub fn keep_alive_interval(mut self, keep_alive_interval: Duration) -> ScanBuilder {
+        self.keep_alive_interval = keep_alive_interval;
+        self
+    }
+
+    /// Sets the lower bound primary key of the scan.
+    ///
+    /// The bound row must have every primary key column set. Tablets which can not hold rows at
+    /// or above the bound are skipped.
+    pub fn primary_key_lower_bound(mut self, bound: RangePartitionBound) -> Result<ScanBuilder> {
+        let mut primary_key = self.encode_primary_key_bound(&bound)?;
+        if let RangePartitionBound::Exclusive(_) = bound {
+            primary_key.push(0);
+        }
+        self.lower_bound = Some(bound);
+        self.lower_bound_primary_key = primary_key;
+        Ok(self)
+    }
+
+    /// Sets the upper bound primary key of the scan.
+    ///
+    /// The bound row must have every primary key column set. Tablets which can not hold rows at
+    /// or below the bound are skipped.
+    pub fn primary_key_upper_bound(mut self, bound: RangePartitionBound) -> Result<ScanBuilder> {
+        let mut primary_key = self.encode_primary_key_bound(&bound)?;
+        if let RangePartitionBound::Inclusive(_) = bound {
+            primary_key.push(0);
+        }
+        self.upper_bound = Some(bound);
+        self.upper_bound_primary_key = primary_key;
+        Ok(self)
+    }
+
+    fn encode_primary_key_bound(&self, bound: &RangePartitionBound) -> Result<Vec<u8>> {
+        if bound.row().schema() != self.table.schema() {
+            return Err(Error::InvalidArgument(
+                "primary key bound schema does not match the table schema".to_owned(),
+            ));
+        }
+        key::encode_primary_key(bound.row())
+    }
+
     pub fn build(self) -> Scan {
         let ScanBuilder {
-            table_schema,
-            table_locations,
-            projected_columns,
+            table,
+            mut projected_columns,
             filters,
+            mut read_mode,
+            fault_tolerant,
+            ordered,
+            parallelism,
+            prefetch,
+            lower_bound,
+            upper_bound,
+            lower_bound_primary_key,
+            upper_bound_primary_key,
+            lower_bound_partition_key,
+            upper_bound_partition_key,
+            tablet_ids,
+            selection,
+            speculation,
+            limit,
+            batch_size_bytes,
+            timeout,
+            keep_alive_interval,
         } = self;
+        let table_schema = table.schema();
+
+        if (fault_tolerant || ordered) && read_mode == ReadMode::Latest {
+            read_mode = ReadMode::Snapshot(None);
+        }
+
+        // Ordered scans merge rows by primary key, so the primary key columns must be projected.
+        let mut key_columns = Vec::new();
+        if ordered {
+            for idx in 0..table_schema.num_primary_key_columns() {
+                match projected_columns.iter().position(|&column| column == idx) {
+                    Some(position) => key_columns.push(position),
+                    None => {
+                        key_columns.push(projected_columns.len());
+                        projected_columns.push(idx);
+                    }
+                }
+            }
+        }
 
         let mut columns = Vec::new();
         for idx in projected_columns {
@@ -110,7 +459,19 @@ impl ScanBuilder {
         }
         let projected_schema = Schema::new(columns, 0);
 
-        let mut short_circuit = false;
+        let mut partition_pruner = PartitionPruner::new(
+            table_schema,
+            table.partition_schema(),
+            &filters,
+            lower_bound.as_ref(),
+            upper_bound.as_ref(),
+        );
+        partition_pruner
+            .intersect_partition_key_range(&lower_bound_partition_key, &upper_bound_partition_key);
+
+        let mut short_circuit = limit == Some(0)
+            || (!upper_bound_primary_key.is_empty()
+                && lower_bound_primary_key >= upper_bound_primary_key);
         let mut predicates = Vec::with_capacity(filters.len());
         for (idx, filter) in filters {
             if filter == Filter::None {
@@ -120,24 +481,209 @@ impl ScanBuilder {
             }
         }
 
-        let state = if short_circuit {
-            ScannerState::Finished
-        } else {
-            ScannerState::Lookup(table_locations.entry(&[]))
+        if short_circuit {
+            partition_pruner = PartitionPruner::empty();
+        }
+
+        let table_locations = table.table_locations().clone();
+        let state = match partition_pruner.next_partition_key(&[]) {
+            Some(partition_key) if ordered => ScannerState::Merge(MergeScan {
+                lookup: Some(table_locations.entry(partition_key)),
+                tablets: Vec::new(),
+                inputs: Vec::new(),
+                heap: BinaryHeap::new(),
+            }),
+            Some(partition_key) if parallelism > 1 => ScannerState::Parallel(ParallelScan {
+                lookup: Some(table_locations.entry(partition_key)),
+                scans: Vec::new(),
+                next: 0,
+            }),
+            Some(partition_key) => ScannerState::Lookup(table_locations.entry(partition_key)),
+            None => ScannerState::Finished,
         };
         Scan {
             projected_schema,
             predicates,
+            lower_bound_primary_key,
+            upper_bound_primary_key,
+            read_mode,
+            fault_tolerant,
+            ordered,
+            key_columns,
+            parallelism,
+            prefetch,
+            tablet_ids,
+            selection,
+            speculation,
+            limit,
+            batch_size_bytes,
+            timeout,
+            keep_alive_interval,
+            client: table.client().clone(),
             table_locations,
+            partition_pruner,
             state,
         }
     }
+
+    /// Builds scan tokens for the scan, one for each tablet which may hold matching rows.
+    ///
+    /// Scan tokens can be serialized and sent to other processes, where they are rehydrated into
+    /// scans of a single tablet. Tokens of `ReadMode::Snapshot(None)` scans are read at a snapshot
+    /// chosen independently for each tablet, so a snapshot timestamp should be provided if the
+    /// tablets must be read at the same point in time.
+    pub fn build_tokens(self) -> impl Future<Item = Vec<ScanToken>, Error = Error> {
+        let table_name = self.table.name().to_owned();
+        let scan = self.build();
+        let pb = scan.token_pb(table_name);
+        let lookup = scan
+            .partition_pruner
+            .next_partition_key(&[])
+            .map(|partition_key| scan.table_locations.entry(partition_key));
+        ScanTokens {
+            scan,
+            pb,
+            lookup,
+            tokens: Vec::new(),
+        }
+    }
+}
+
+/// A future which resolves to the scan tokens of each tablet which may hold matching rows.
+struct ScanTokens {
+    scan: Scan,
+    pb: ScanTokenPb,
+    lookup: Option<Lookup<Entry>>,
+    tokens: Vec<ScanToken>,
+}
+
+impl Future for ScanTokens {
+    type Item = Vec<ScanToken>;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Vec<ScanToken>, Error> {
+        loop {
+            let entry = match self.lookup.as_mut() {
+                Some(lookup) => try_ready!(lookup.poll()),
+                None => return Ok(Async::Ready(mem::replace(&mut self.tokens, Vec::new()))),
+            };
+
+            match entry {
+                Entry::Tablet(ref tablet) if self.scan.is_scanned(tablet) => {
+                    let mut pb = self.pb.clone();
+                    pb.lower_bound_partition_key = Some(tablet.lower_bound().to_vec());
+                    pb.upper_bound_partition_key = Some(tablet.upper_bound().to_vec());
+                    self.tokens.push(ScanToken {
+                        tablet_id: tablet.id(),
+                        pb,
+                    });
+                }
+                _ => (),
+            }
+
+            self.lookup = self.scan.next_lookup(entry.upper_bound());
+        }
+    }
+}
+
+/// A serializable description of a scan of a single tablet.
+///
+/// Scan tokens are created with `ScanBuilder::build_tokens`. The serialized form is compatible
+/// with the scan tokens of the other Kudu clients, which identify the tablet by its partition key
+/// range.
+///
+/// Tokens carry the projection, filters, primary key bounds, limit, read mode, snapshot timestamp
+/// and fault tolerance of the scan. The token format has no fields for the options which control
+/// how the scan is executed, so `ordered`, `replica_selection`, `speculation`, `batch_size_bytes`,
+/// `timeout`, `prefetch`, `parallel` and `keep_alive_interval` are not carried, and rehydrated
+/// scans use their defaults. Use `ScanToken::into_scan_builder` to set them before scanning.
+#[derive(Clone, Debug)]
+pub struct ScanToken {
+    tablet_id: TabletId,
+    pb: ScanTokenPb,
+}
+
+impl ScanToken {
+    /// Returns the ID of the tablet scanned by the token.
+    pub fn tablet_id(&self) -> TabletId {
+        self.tablet_id
+    }
+
+    /// Serializes the scan token.
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(self.pb.encoded_len());
+        self.pb.encode(&mut buf).expect("failed to encode scan token");
+        buf
+    }
+
+    /// Rehydrates the scan token into a scan.
+    pub fn into_scan(self, client: &Client) -> impl Future<Item = Scan, Error = Error> {
+        self.into_scan_builder(client).map(ScanBuilder::build)
+    }
+
+    /// Rehydrates the scan token into a scan builder, so that the options which are not carried
+    /// by the token can be set.
+    pub fn into_scan_builder(
+        self,
+        client: &Client,
+    ) -> impl Future<Item = ScanBuilder, Error = Error> {
+        builder_from_token_pb(self.pb, client.clone())
+    }
+
+    /// Deserializes a scan token and rehydrates it into a scan.
+    pub fn deserialize_into_scan(
+        client: &Client,
+        token: &[u8],
+    ) -> impl Future<Item = Scan, Error = Error> {
+        ScanToken::deserialize_into_scan_builder(client, token).map(ScanBuilder::build)
+    }
+
+    /// Deserializes a scan token and rehydrates it into a scan builder, so that the options which
+    /// are not carried by the token can be set.
+    pub fn deserialize_into_scan_builder(
+        client: &Client,
+        token: &[u8],
+    ) -> impl Future<Item = ScanBuilder, Error = Error> {
+        let client = client.clone();
+        future::result(
+            ScanTokenPb::decode(token).map_err(|error| Error::Serialization(error.to_string())),
+        ).and_then(move |pb| builder_from_token_pb(pb, client))
+    }
+}
+
+fn builder_from_token_pb(
+    pb: ScanTokenPb,
+    mut client: Client,
+) -> impl Future<Item = ScanBuilder, Error = Error> {
+    future::result(pb.table_name.clone().expect_field("ScanTokenPB", "table_name"))
+        .and_then(move |table_name| client.open_table(table_name))
+        .and_then(move |table| ScanBuilder::from_token_pb(table, pb))
 }
 
 pub struct Scan {
     projected_schema: Schema,
     predicates: Vec<ColumnPredicatePb>,
+    lower_bound_primary_key: Vec<u8>,
+    upper_bound_primary_key: Vec<u8>,
+    read_mode: ReadMode,
+    fault_tolerant: bool,
+    ordered: bool,
+    /// The projected primary key columns, if the scan is ordered.
+    key_columns: Vec<usize>,
+    parallelism: usize,
+    prefetch: usize,
+    /// The tablets to scan, if the scan is restricted to a subset of the tablets.
+    tablet_ids: Option<HashSet<TabletId>>,
+    selection: Selection,
+    speculation: Speculation,
+    /// The number of rows remaining before the limit is reached.
+    limit: Option<u64>,
+    batch_size_bytes: Option<u32>,
+    timeout: Duration,
+    keep_alive_interval: Duration,
+    client: Client,
     table_locations: TableLocations,
+    partition_pruner: PartitionPruner,
     state: ScannerState,
 }
 
@@ -147,12 +693,74 @@ enum ScannerState {
         tablet: Arc<Tablet>,
         tablet_scan: TabletScan,
     },
+    Merge(MergeScan),
+    Parallel(ParallelScan),
     Finished,
 }
 
+/// The state of a parallel scan, which scans multiple tablets concurrently.
+struct ParallelScan {
+    /// Looks up the location of the next tablet to scan.
+    lookup: Option<Lookup<Entry>>,
+    scans: Vec<TabletScan>,
+    /// The index of the scan to poll first, which rotates in order to poll the scans fairly.
+    next: usize,
+}
+
+/// The state of an ordered scan, which merges the rows of every tablet by primary key.
+struct MergeScan {
+    /// Looks up the locations of the tablets to scan.
+    lookup: Option<Lookup<Entry>>,
+    /// The located tablets which have not been opened.
+    tablets: Vec<Arc<Tablet>>,
+    inputs: Vec<MergeInput>,
+    /// The encoded primary key of the next row of each input which holds a batch, and the index
+    /// of the input. The key buffers are reused for the following rows of the input.
+    heap: BinaryHeap<Reverse<(Vec<u8>, usize)>>,
+}
+
+struct MergeInput {
+    tablet_scan: TabletScan,
+    /// The batch holding the next row of the tablet.
+    batch: Option<RowBatch>,
+    /// The offset of the next row in the batch.
+    offset: usize,
+    is_finished: bool,
+}
+
+impl MergeInput {
+    /// Encodes the primary key of the next row of the tablet into the buffer.
+    fn encode_key(&self, key_columns: &[usize], buf: &mut Vec<u8>) -> Result<()> {
+        let batch = self.batch.as_ref().expect("merge input has no batch");
+        buf.clear();
+        key::encode_key(&batch.row(self.offset), key_columns, buf)
+    }
+
+    /// Polls the tablet for its next non-empty batch of rows, and returns `true` if the input
+    /// holds a batch.
+    fn poll_batch(&mut self) -> Poll<bool, Error> {
+        while !self.is_finished {
+            match try_ready!(self.tablet_scan.poll()) {
+                Some(ref batch) if batch.num_rows() == 0 => (),
+                Some(batch) => {
+                    self.batch = Some(batch);
+                    self.offset = 0;
+                    return Ok(Async::Ready(true));
+                }
+                None => self.is_finished = true,
+            }
+        }
+        Ok(Async::Ready(false))
+    }
+}
+
+/// The size of the row data of merged batches, unless the scan's batch size is set. Merged batches
+/// are returned before reaching the size if a tablet's next batch has not arrived.
+const MERGE_BATCH_SIZE_BYTES: usize = 1024 * 1024;
+
 impl Scan {
     fn new_scan_request(&self, tablet: TabletId) -> NewScanRequestPb {
-        NewScanRequestPb {
+        let mut request = NewScanRequestPb {
             tablet_id: tablet.to_string().into_bytes(),
             projected_columns: self
                 .projected_schema
@@ -162,96 +770,476 @@ impl Scan {
                 .collect(),
             column_predicates: self.predicates.clone(),
             ..Default::default()
+        };
+
+        if !self.lower_bound_primary_key.is_empty() {
+            request.start_primary_key = Some(self.lower_bound_primary_key.clone());
+        }
+        if !self.upper_bound_primary_key.is_empty() {
+            request.stop_primary_key = Some(self.upper_bound_primary_key.clone());
         }
-    }
 
-    pub fn projected_schema(&self) -> &Schema {
-        &self.projected_schema
+        match self.read_mode {
+            ReadMode::Latest => (),
+            ReadMode::Snapshot(timestamp) => {
+                request.read_mode = Some(ReadModePb::ReadAtSnapshot as i32);
+                request.snap_timestamp = timestamp;
+            }
+            ReadMode::ReadYourWrites => {
+                request.read_mode = Some(ReadModePb::ReadYourWrites as i32);
+            }
+        }
+
+        let timestamp = self.client.latest_observed_timestamp();
+        if timestamp > 0 {
+            request.propagated_timestamp = Some(timestamp);
+        }
+
+        if self.fault_tolerant || self.ordered {
+            request.order_mode = Some(OrderModePb::Ordered as i32);
+        }
+
+        request.limit = self.limit;
+
+        request
     }
-}
 
-impl Stream for Scan {
-    type Item = RowBatch;
-    type Error = Error;
+    fn token_pb(&self, table_name: String) -> ScanTokenPb {
+        let mut pb = ScanTokenPb {
+            table_name: Some(table_name),
+            projected_columns: self
+                .projected_schema
+                .columns()
+                .iter()
+                .map(column_to_pb)
+                .collect(),
+            column_predicates: self.predicates.clone(),
+            limit: self.limit,
+            fault_tolerant: Some(self.fault_tolerant),
+            ..Default::default()
+        };
 
-    fn poll(&mut self) -> Poll<Option<RowBatch>, Error> {
-        trace!("Scan::poll");
-        loop {
-            match mem::replace(&mut self.state, ScannerState::Finished) {
-                ScannerState::Lookup(mut lookup) => match lookup.poll()? {
-                    Async::Ready(Entry::Tablet(tablet)) => {
-                        let tablet_scan = TabletScan::new(
-                            self.projected_schema.clone(),
-                            tablet.clone(),
-                            self.new_scan_request(tablet.id()),
-                        );
-                        self.state = ScannerState::Scan {
-                            tablet,
-                            tablet_scan,
-                        };
-                    }
-                    Async::Ready(Entry::NonCoveredRange { upper_bound, .. }) => {
-                        if !upper_bound.is_empty() {
-                            let lookup = self.table_locations.entry(&upper_bound);
-                            self.state = ScannerState::Lookup(lookup);
-                        }
-                    }
-                    Async::NotReady => {
-                        self.state = ScannerState::Lookup(lookup);
-                        return Ok(Async::NotReady);
-                    }
-                },
-                ScannerState::Scan {
-                    tablet,
-                    mut tablet_scan,
-                } => match tablet_scan.poll()? {
-                    Async::Ready(Some(batch)) => {
-                        self.state = ScannerState::Scan {
-                            tablet,
-                            tablet_scan,
-                        };
-                        return Ok(Async::Ready(Some(batch)));
-                    }
-                    Async::Ready(None) => if !tablet.upper_bound().is_empty() {
-                        let lookup = self.table_locations.entry(tablet.upper_bound());
-                        self.state = ScannerState::Lookup(lookup);
-                    },
-                    Async::NotReady => {
-                        self.state = ScannerState::Scan {
-                            tablet,
-                            tablet_scan,
-                        };
-                        return Ok(Async::NotReady);
-                    }
-                },
-                ScannerState::Finished => return Ok(Async::Ready(None)),
+        if !self.lower_bound_primary_key.is_empty() {
+            pb.lower_bound_primary_key = Some(self.lower_bound_primary_key.clone());
+        }
+        if !self.upper_bound_primary_key.is_empty() {
+            pb.upper_bound_primary_key = Some(self.upper_bound_primary_key.clone());
+        }
+
+        match self.read_mode {
+            ReadMode::Latest => (),
+            ReadMode::Snapshot(timestamp) => {
+                pb.read_mode = Some(ReadModePb::ReadAtSnapshot as i32);
+                pb.snap_timestamp = timestamp;
+            }
+            ReadMode::ReadYourWrites => {
+                pb.read_mode = Some(ReadModePb::ReadYourWrites as i32);
             }
         }
+
+        let timestamp = self.client.latest_observed_timestamp();
+        if timestamp > 0 {
+            pb.propagated_timestamp = Some(timestamp);
+        }
+
+        pb
     }
-}
 
-impl fmt::Debug for Scan {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        f.debug_struct("Scan").finish()
+    /// Returns a lookup of the next table location which may hold matching rows, following the
+    /// location ending at `upper_bound`.
+    fn next_lookup(&self, upper_bound: &[u8]) -> Option<Lookup<Entry>> {
+        if upper_bound.is_empty() {
+            return None;
+        }
+        self.partition_pruner
+            .next_partition_key(upper_bound)
+            .map(|partition_key| self.table_locations.entry(partition_key))
     }
-}
 
-pub struct RowBatch {
-    projected_schema: Schema,
-    len: usize,
-    data: Bytes,
-    _indirect_data: Bytes,
-}
+    /// Returns `true` if the scan includes the tablet.
+    fn is_scanned(&self, tablet: &Tablet) -> bool {
+        self.tablet_ids
+            .as_ref()
+            .map_or(true, |tablet_ids| tablet_ids.contains(&tablet.id()))
+    }
 
-impl RowBatch {
-    fn new(
-        projected_schema: Schema,
-        block: &RowwiseRowBlockPb,
-        mut sidecars: Vec<BytesMut>,
-    ) -> Result<RowBatch> {
-        trace!(
-            "RowBatch::new; block: {:?}, sidecars: {:?}",
-            block,
+    /// Enforces the row limit on a batch. Returns `true` if the limit has been reached.
+    fn apply_limit(&mut self, batch: &mut RowBatch) -> bool {
+        match self.limit {
+            Some(limit) if batch.num_rows() as u64 >= limit => {
+                batch.truncate(limit as usize);
+                self.limit = Some(0);
+                true
+            }
+            Some(limit) => {
+                self.limit = Some(limit - batch.num_rows() as u64);
+                false
+            }
+            None => false,
+        }
+    }
+
+    /// Polls an ordered scan for the next batch of merged rows.
+    fn poll_merge(&mut self, merge: &mut MergeScan) -> Poll<Option<RowBatch>, Error> {
+        // Locate the tablets.
+        while let Some(lookup) = merge.lookup.as_mut() {
+            let entry = try_ready!(lookup.poll());
+            match entry {
+                Entry::Tablet(ref tablet) if self.is_scanned(tablet) => {
+                    merge.tablets.push(tablet.clone())
+                }
+                _ => (),
+            }
+            merge.lookup = self.next_lookup(entry.upper_bound());
+        }
+
+        loop {
+            // Open the tablet scans. Unless the snapshot timestamp is pinned, the first tablet is
+            // opened alone, and the remaining tablets are opened at the snapshot timestamp chosen
+            // by its tablet server.
+            if !merge.tablets.is_empty() {
+                let is_pinned = match self.read_mode {
+                    ReadMode::Snapshot(Some(_)) => true,
+                    _ => false,
+                };
+                let has_responded = merge
+                    .inputs
+                    .iter()
+                    .any(|input| input.batch.is_some() || input.is_finished);
+                let num_tablets = if is_pinned || has_responded {
+                    merge.tablets.len()
+                } else if merge.inputs.is_empty() {
+                    1
+                } else {
+                    0
+                };
+                for tablet in merge.tablets.drain(..num_tablets) {
+                    merge.inputs.push(MergeInput {
+                        tablet_scan: TabletScan::new(self, tablet),
+                        batch: None,
+                        offset: 0,
+                        is_finished: false,
+                    });
+                }
+            }
+
+            // Wait until every unfinished tablet has a batch of rows.
+            let mut is_ready = true;
+            for (i, input) in merge.inputs.iter_mut().enumerate() {
+                if input.batch.is_some() || input.is_finished {
+                    continue;
+                }
+                let poll = input.poll_batch()?;
+                self.observe_snapshot_timestamp(input.tablet_scan.snapshot_timestamp());
+                match poll {
+                    Async::Ready(true) => {
+                        let mut key = Vec::new();
+                        input.encode_key(&self.key_columns, &mut key)?;
+                        merge.heap.push(Reverse((key, i)));
+                    }
+                    Async::Ready(false) => (),
+                    Async::NotReady => is_ready = false,
+                }
+            }
+            if !is_ready {
+                return Ok(Async::NotReady);
+            }
+            if merge.tablets.is_empty() {
+                break;
+            }
+        }
+
+        if merge.heap.is_empty() {
+            return Ok(Async::Ready(None));
+        }
+
+        // Merge rows until the merged batch is full, or until a batch is exhausted and the next
+        // batch of that tablet has not arrived, since it is required to continue.
+        let batch_size_bytes = self
+            .batch_size_bytes
+            .map_or(MERGE_BATCH_SIZE_BYTES, |size| size as usize);
+        let mut data = BytesMut::new();
+        let mut len = 0;
+        // The merged rows point into the indirect data of the batches they were copied from.
+        let mut indirect_data = Vec::new();
+        let mut is_copied = vec![false; merge.inputs.len()];
+        while data.len() < batch_size_bytes {
+            let Reverse((mut key, i)) = match merge.heap.pop() {
+                Some(entry) => entry,
+                None => break,
+            };
+            let input = &mut merge.inputs[i];
+            let is_exhausted = {
+                let batch = input.batch.as_ref().unwrap();
+                data.extend_from_slice(batch.row_data(input.offset));
+                input.offset += 1;
+                if input.offset == batch.num_rows() {
+                    indirect_data.extend(batch.indirect_data.iter().cloned());
+                    true
+                } else {
+                    false
+                }
+            };
+            len += 1;
+
+            if is_exhausted {
+                input.batch = None;
+                is_copied[i] = false;
+                let poll = input.poll_batch()?;
+                self.observe_snapshot_timestamp(input.tablet_scan.snapshot_timestamp());
+                match poll {
+                    Async::Ready(true) => (),
+                    Async::Ready(false) => continue,
+                    Async::NotReady => break,
+                }
+            } else {
+                is_copied[i] = true;
+            }
+            input.encode_key(&self.key_columns, &mut key)?;
+            merge.heap.push(Reverse((key, i)));
+        }
+
+        for (input, is_copied) in merge.inputs.iter().zip(is_copied) {
+            if is_copied {
+                let batch = input.batch.as_ref().unwrap();
+                indirect_data.extend(batch.indirect_data.iter().cloned());
+            }
+        }
+
+        Ok(Async::Ready(Some(RowBatch {
+            projected_schema: self.projected_schema.clone(),
+            len,
+            data: data.freeze(),
+            indirect_data,
+        })))
+    }
+
+    /// Polls a parallel scan for the next batch returned by any of the tablets.
+    fn poll_parallel(&mut self, parallel: &mut ParallelScan) -> Poll<Option<RowBatch>, Error> {
+        loop {
+            // Unless the snapshot timestamp is pinned, the first tablet is scanned alone.
+            let parallelism = match self.read_mode {
+                ReadMode::Snapshot(None) | ReadMode::ReadYourWrites => 1,
+                _ => self.parallelism,
+            };
+
+            // Open tablet scans up to the concurrency limit.
+            while parallel.scans.len() < parallelism {
+                let entry = match parallel.lookup.as_mut() {
+                    Some(lookup) => match lookup.poll()? {
+                        Async::Ready(entry) => entry,
+                        Async::NotReady => break,
+                    },
+                    None => break,
+                };
+                parallel.lookup = self.next_lookup(entry.upper_bound());
+                match entry {
+                    Entry::Tablet(ref tablet) if self.is_scanned(tablet) => {
+                        parallel.scans.push(TabletScan::new(self, tablet.clone()))
+                    }
+                    _ => (),
+                }
+            }
+
+            if parallel.scans.is_empty() {
+                return if parallel.lookup.is_none() {
+                    Ok(Async::Ready(None))
+                } else {
+                    Ok(Async::NotReady)
+                };
+            }
+
+            let num_scans = parallel.scans.len();
+            let mut finished = None;
+            for n in 0..num_scans {
+                let i = (parallel.next + n) % num_scans;
+                match parallel.scans[i].poll()? {
+                    Async::Ready(Some(batch)) => {
+                        self.observe_snapshot_timestamp(parallel.scans[i].snapshot_timestamp());
+                        parallel.next = i + 1;
+                        return Ok(Async::Ready(Some(batch)));
+                    }
+                    Async::Ready(None) => {
+                        finished = Some(i);
+                        break;
+                    }
+                    Async::NotReady => (),
+                }
+            }
+
+            // If a tablet scan has finished, another tablet can be opened in its place.
+            match finished {
+                Some(i) => {
+                    let tablet_scan = parallel.scans.swap_remove(i);
+                    self.observe_snapshot_timestamp(tablet_scan.snapshot_timestamp());
+                }
+                None => return Ok(Async::NotReady),
+            }
+        }
+    }
+
+    /// Pins the snapshot timestamp chosen by a tablet server, so that the remaining tablets are
+    /// scanned at the same point in time.
+    fn observe_snapshot_timestamp(&mut self, timestamp: Option<u64>) {
+        match (self.read_mode, timestamp) {
+            (ReadMode::Snapshot(None), Some(timestamp))
+            | (ReadMode::ReadYourWrites, Some(timestamp)) => {
+                self.read_mode = ReadMode::Snapshot(Some(timestamp));
+            }
+            _ => (),
+        }
+    }
+
+    pub fn projected_schema(&self) -> &Schema {
+        &self.projected_schema
+    }
+
+    /// Closes the scan, releasing the open tablet server scanner, if any.
+    ///
+    /// Dropping a scan also closes the scanner, but does not wait for the tablet server to
+    /// acknowledge the close.
+    pub fn close(mut self) -> impl Future<Item = (), Error = Error> {
+        let rpcs = match mem::replace(&mut self.state, ScannerState::Finished) {
+            ScannerState::Scan {
+                mut tablet_scan, ..
+            } => tablet_scan.close().into_iter().collect(),
+            ScannerState::Merge(mut merge) => merge
+                .inputs
+                .iter_mut()
+                .filter_map(|input| input.tablet_scan.close())
+                .collect(),
+            ScannerState::Parallel(mut parallel) => parallel
+                .scans
+                .iter_mut()
+                .filter_map(TabletScan::close)
+                .collect(),
+            _ => Vec::new(),
+        };
+        future::join_all(rpcs).map(|_| ())
+    }
+
+    /// Returns the read mode of the scan.
+    ///
+    /// Once the first tablet has been scanned, snapshot and read-your-writes scans return the
+    /// pinned snapshot read mode.
+    pub fn read_mode(&self) -> ReadMode {
+        self.read_mode
+    }
+}
+
+impl Stream for Scan {
+    type Item = RowBatch;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Option<RowBatch>, Error> {
+        trace!("Scan::poll");
+        loop {
+            match mem::replace(&mut self.state, ScannerState::Finished) {
+                ScannerState::Lookup(mut lookup) => match lookup.poll()? {
+                    Async::Ready(Entry::Tablet(ref tablet)) if !self.is_scanned(tablet) => {
+                        self.state = self
+                            .next_lookup(tablet.upper_bound())
+                            .map_or(ScannerState::Finished, ScannerState::Lookup);
+                    }
+                    Async::Ready(Entry::Tablet(tablet)) => {
+                        let tablet_scan = TabletScan::new(self, tablet.clone());
+                        self.state = ScannerState::Scan {
+                            tablet,
+                            tablet_scan,
+                        };
+                    }
+                    Async::Ready(Entry::NonCoveredRange { upper_bound, .. }) => {
+                        self.state = self
+                            .next_lookup(&upper_bound)
+                            .map_or(ScannerState::Finished, ScannerState::Lookup);
+                    }
+                    Async::NotReady => {
+                        self.state = ScannerState::Lookup(lookup);
+                        return Ok(Async::NotReady);
+                    }
+                },
+                ScannerState::Scan {
+                    tablet,
+                    mut tablet_scan,
+                } => match tablet_scan.poll()? {
+                    Async::Ready(Some(mut batch)) => {
+                        self.observe_snapshot_timestamp(tablet_scan.snapshot_timestamp());
+                        if !self.apply_limit(&mut batch) {
+                            self.state = ScannerState::Scan {
+                                tablet,
+                                tablet_scan,
+                            };
+                        }
+                        return Ok(Async::Ready(Some(batch)));
+                    }
+                    Async::Ready(None) => {
+                        self.state = self
+                            .next_lookup(tablet.upper_bound())
+                            .map_or(ScannerState::Finished, ScannerState::Lookup);
+                    }
+                    Async::NotReady => {
+                        self.state = ScannerState::Scan {
+                            tablet,
+                            tablet_scan,
+                        };
+                        return Ok(Async::NotReady);
+                    }
+                },
+                ScannerState::Merge(mut merge) => match self.poll_merge(&mut merge)? {
+                    Async::Ready(Some(mut batch)) => {
+                        if !self.apply_limit(&mut batch) {
+                            self.state = ScannerState::Merge(merge);
+                        }
+                        return Ok(Async::Ready(Some(batch)));
+                    }
+                    Async::Ready(None) => return Ok(Async::Ready(None)),
+                    Async::NotReady => {
+                        self.state = ScannerState::Merge(merge);
+                        return Ok(Async::NotReady);
+                    }
+                },
+                ScannerState::Parallel(mut parallel) => match self.poll_parallel(&mut parallel)? {
+                    Async::Ready(Some(mut batch)) => {
+                        if !self.apply_limit(&mut batch) {
+                            self.state = ScannerState::Parallel(parallel);
+                        }
+                        return Ok(Async::Ready(Some(batch)));
+                    }
+                    Async::Ready(None) => return Ok(Async::Ready(None)),
+                    Async::NotReady => {
+                        self.state = ScannerState::Parallel(parallel);
+                        return Ok(Async::NotReady);
+                    }
+                },
+                ScannerState::Finished => return Ok(Async::Ready(None)),
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Scan {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Scan").finish()
+    }
+}
+
+pub struct RowBatch {
+    projected_schema: Schema,
+    len: usize,
+    data: Bytes,
+    /// The buffers holding the string and binary values of the rows.
+    indirect_data: Vec<Bytes>,
+}
+
+impl RowBatch {
+    fn new(
+        projected_schema: Schema,
+        block: &RowwiseRowBlockPb,
+        mut sidecars: Vec<BytesMut>,
+    ) -> Result<RowBatch> {
+        trace!(
+            "RowBatch::new; block: {:?}, sidecars: {:?}",
+            block,
             sidecars
         );
         let mut data = match block.rows_sidecar {
@@ -335,7 +1323,7 @@ impl RowBatch {
             projected_schema,
             len: block.num_rows() as usize,
             data: data.freeze(),
-            _indirect_data: indirect_data,
+            indirect_data: vec![indirect_data],
         })
     }
 
@@ -343,19 +1331,111 @@ impl RowBatch {
         self.len
     }
 
+    fn row_len(&self) -> usize {
+        self.projected_schema.row_len()
+            + self.projected_schema.has_nullable_columns() as usize
+                * self.projected_schema.bitmap_len()
+    }
+
+    /// Returns the encoded data of the row at `idx`.
+    fn row_data(&self, idx: usize) -> &[u8] {
+        let row_len = self.row_len();
+        &self.data[idx * row_len..(idx + 1) * row_len]
+    }
+
+    fn row(&self, idx: usize) -> Row {
+        Row::contiguous(self.projected_schema.clone(), self.row_data(idx))
+    }
+
+    /// Shortens the batch to the first `len` rows.
+    fn truncate(&mut self, len: usize) {
+        if len < self.len {
+            let row_len = self.row_len();
+            self.data.truncate(len * row_len);
+            self.len = len;
+        }
+    }
+
     pub fn projected_schema(&self) -> &Schema {
         &self.projected_schema
     }
+
+    /// Returns an iterator over the values of a column, which yields `None` for null values.
+    ///
+    /// Returns an error if the column is not projected, the column type does not match the value
+    /// type, or a value of a string column is not valid UTF-8.
+    pub fn column<'a, V, C>(&'a self, column: C) -> Result<ColumnIter<'a, V>>
+    where
+        V: Value<'a>,
+        C: ColumnSelector,
+    {
+        let idx = column.column_index(&self.projected_schema)?;
+        let column = &self.projected_schema.columns()[idx];
+        if !V::can_read_from(column.data_type()) || V::is_nullable() {
+            return Err(Error::InvalidArgument(format!(
+                "type {:?} is invalid for column {:?}",
+                V::DATA_TYPE,
+                column
+            )));
+        }
+        let is_null_offset = if column.is_nullable() {
+            Some(self.projected_schema.row_len() + (idx >> 3))
+        } else {
+            None
+        };
+        let iter = ColumnIter {
+            rows: self.data.chunks(self.row_len()),
+            offset: self.projected_schema.column_offset(idx) as usize,
+            is_null_offset,
+            is_null_mask: 1 << (idx & 7),
+            is_var_len: column.data_type().is_var_len(),
+            _marker: PhantomData,
+        };
+        // Check string values up front, so that the iterator is infallible and reads the values
+        // without checking them again.
+        if V::DATA_TYPE == DataType::String {
+            for row in iter.rows.clone() {
+                if !iter.is_null(row) {
+                    ::std::str::from_utf8(unsafe { iter.var_len_value(row) })?;
+                }
+            }
+        }
+        Ok(iter)
+    }
+}
+
+/// Returns the rows of the batch with a `'static` lifetime.
+///
+/// Instead of copying the rows, the rows share the buffers of the batch through reference
+/// counting. The buffers are freed once every row has been dropped.
+impl IntoIterator for RowBatch {
+    type Item = Row<'static>;
+    type IntoIter = RowBatchIntoIter;
+    fn into_iter(self) -> RowBatchIntoIter {
+        let row_len = self.row_len();
+        let RowBatch {
+            projected_schema,
+            len,
+            data,
+            indirect_data,
+        } = self;
+        RowBatchIntoIter {
+            projected_schema,
+            row_len,
+            rows: 0..len,
+            data: Arc::new(RowBatchData {
+                data,
+                _indirect_data: indirect_data,
+            }),
+        }
+    }
 }
 
 impl<'a> IntoIterator for &'a RowBatch {
     type Item = Row<'a>;
     type IntoIter = RowBatchIter<'a>;
     fn into_iter(self) -> RowBatchIter<'a> {
-        let projected_schema = &self.projected_schema;
-        let row_len = projected_schema.row_len()
-            + projected_schema.has_nullable_columns() as usize * projected_schema.bitmap_len();
-        let iter = self.data.chunks(row_len);
+        let iter = self.data.chunks(self.row_len());
         RowBatchIter {
             projected_schema: &self.projected_schema,
             iter,
@@ -394,132 +1474,547 @@ impl<'a> DoubleEndedIterator for RowBatchIter<'a> {
 // TODO: compile-time assert that Chunks is fused.
 impl<'a> FusedIterator for RowBatchIter<'a> {}
 
-enum TabletScan {
-    New {
-        projected_schema: Schema,
-        rpc: ReplicaRpc<Arc<Tablet>, ScanRequestPb, ScanResponsePb>,
-    },
+/// An iterator over the values of a column in a row batch.
+pub struct ColumnIter<'a, V> {
+    rows: ::std::slice::Chunks<'a, u8>,
+    /// The offset of the column value in the row data.
+    offset: usize,
+    /// The offset of the null bitmap byte holding the column's bit, if the column is nullable.
+    is_null_offset: Option<usize>,
+    is_null_mask: u8,
+    /// Whether the column values are stored in the indirect data.
+    is_var_len: bool,
+    _marker: PhantomData<V>,
+}
+
+impl<'a, V> ColumnIter<'a, V>
+where
+    V: Value<'a>,
+{
+    fn is_null(&self, row: &[u8]) -> bool {
+        match self.is_null_offset {
+            Some(offset) => row[offset] & self.is_null_mask != 0,
+            None => false,
+        }
+    }
+
+    /// Returns the bytes of a variable length value.
+    unsafe fn var_len_value(&self, row: &'a [u8]) -> &'a [u8] {
+        let (ptr, len, _) = read_var_len_value(row[self.offset..].as_ptr());
+        slice::from_raw_parts(ptr, len)
+    }
+
+    fn value(&self, row: &'a [u8]) -> Option<V> {
+        if self.is_null(row) {
+            None
+        } else if self.is_var_len {
+            // String values have been checked to be valid UTF-8 when the iterator was created.
+            unsafe { Some(V::decode(self.var_len_value(row))) }
+        } else {
+            // The column type has been checked against the value type, so reading a fixed length
+            // value does not fail.
+            unsafe { Some(V::read_cell(row[self.offset..].as_ptr()).unwrap()) }
+        }
+    }
+}
+
+impl<'a, V> Iterator for ColumnIter<'a, V>
+where
+    V: Value<'a>,
+{
+    type Item = Option<V>;
+    fn next(&mut self) -> Option<Option<V>> {
+        self.rows.next().map(|row| self.value(row))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.rows.size_hint()
+    }
+}
+
+impl<'a, V> ExactSizeIterator for ColumnIter<'a, V> where V: Value<'a> {}
+
+impl<'a, V> DoubleEndedIterator for ColumnIter<'a, V>
+where
+    V: Value<'a>,
+{
+    fn next_back(&mut self) -> Option<Option<V>> {
+        self.rows.next_back().map(|row| self.value(row))
+    }
+}
+
+impl<'a, V> FusedIterator for ColumnIter<'a, V> where V: Value<'a> {}
+
+/// The buffers of a row batch, shared by the rows returned by `RowBatchIntoIter`.
+pub(crate) struct RowBatchData {
+    data: Bytes,
+    /// Holds the string and binary values referenced by the rows.
+    _indirect_data: Vec<Bytes>,
+}
+
+pub struct RowBatchIntoIter {
+    projected_schema: Schema,
+    row_len: usize,
+    rows: Range<usize>,
+    data: Arc<RowBatchData>,
+}
+
+impl RowBatchIntoIter {
+    fn row(&self, idx: usize) -> Row<'static> {
+        let data = &self.data.data[idx * self.row_len..(idx + 1) * self.row_len];
+        Row::shared(self.projected_schema.clone(), data, self.data.clone())
+    }
+}
+
+impl Iterator for RowBatchIntoIter {
+    type Item = Row<'static>;
+    fn next(&mut self) -> Option<Row<'static>> {
+        self.rows.next().map(|idx| self.row(idx))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.rows.size_hint()
+    }
+}
+
+impl ExactSizeIterator for RowBatchIntoIter {}
+
+impl DoubleEndedIterator for RowBatchIntoIter {
+    fn next_back(&mut self) -> Option<Row<'static>> {
+        self.rows.next_back().map(|idx| self.row(idx))
+    }
+}
+
+impl FusedIterator for RowBatchIntoIter {}
+
+/// Returns true if the error indicates that the tablet server holding a scanner has failed, or
+/// has lost the scanner. Fault tolerant scans recover from these errors by resuming the scan on
+/// another replica.
+fn is_replica_failure(error: &Error) -> bool {
+    match *error {
+        Error::Io(..) | Error::TimedOut | Error::Compound(..) => true,
+        Error::Rpc(ref error) => error.is_retriable(),
+        Error::TabletServer(ref error) => match error.code {
+            TabletServerErrorCode::ScannerExpired
+            | TabletServerErrorCode::TabletNotFound
+            | TabletServerErrorCode::TabletNotRunning
+            | TabletServerErrorCode::TabletFailed
+            | TabletServerErrorCode::Throttled => true,
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+struct TabletScan {
+    projected_schema: Schema,
+    client: Client,
+    tablet: Arc<Tablet>,
+    /// The snapshot timestamp chosen by the tablet server, if the scan is a snapshot scan.
+    snapshot_timestamp: Option<u64>,
+    /// The original scan request, retained by fault tolerant scans in order to resume the scan.
+    resume_request: Option<NewScanRequestPb>,
+    /// The last primary key returned by the tablet server, if the scan is ordered.
+    last_primary_key: Option<Vec<u8>>,
+    selection: Selection,
+    speculation: Speculation,
+    batch_size_bytes: Option<u32>,
+    timeout: Duration,
+    keep_alive_interval: Duration,
+    /// The keep-alive task of the scanner, if it has been spawned.
+    keep_alive: Option<KeepAliveHandle>,
+    /// The number of batches to fetch ahead of the consumer.
+    prefetch: usize,
+    /// Fetched batches which have not been returned.
+    buffer: VecDeque<RowBatch>,
+    state: TabletScanState,
+}
+
+enum TabletScanState {
+    New(ReplicaRpc<Arc<Tablet>, ScanRequestPb, ScanResponsePb>),
     Continue {
-        projected_schema: Schema,
         scanner_id: ScannerId,
         call_seq_id: u32,
+        proxy: Proxy,
+        /// The index of the tablet replica hosting the scanner.
+        replica: usize,
         rpc: ReplicaRpc<Proxy, ScanRequestPb, ScanResponsePb>,
     },
     Finished,
 }
 
 impl TabletScan {
-    fn new(
-        projected_schema: Schema,
-        tablet: Arc<Tablet>,
-        new_scan_request: NewScanRequestPb,
-    ) -> TabletScan {
+    fn new(scan: &Scan, tablet: Arc<Tablet>) -> TabletScan {
         debug!("TabletScan::new; tablet: {:?}", &*tablet);
+        let new_scan_request = scan.new_scan_request(tablet.id());
+        let snapshot_timestamp = new_scan_request.snap_timestamp;
+        let resume_request = if scan.fault_tolerant {
+            Some(new_scan_request.clone())
+        } else {
+            None
+        };
+        let mut tablet_scan = TabletScan {
+            projected_schema: scan.projected_schema.clone(),
+            client: scan.client.clone(),
+            tablet,
+            snapshot_timestamp,
+            resume_request,
+            last_primary_key: None,
+            selection: scan.selection,
+            speculation: scan.speculation,
+            batch_size_bytes: scan.batch_size_bytes,
+            timeout: scan.timeout,
+            keep_alive_interval: scan.keep_alive_interval,
+            keep_alive: None,
+            prefetch: scan.prefetch,
+            buffer: VecDeque::new(),
+            state: TabletScanState::Finished,
+        };
+        tablet_scan.state = tablet_scan.open(new_scan_request);
+        tablet_scan
+    }
+
+    fn open(&self, new_scan_request: NewScanRequestPb) -> TabletScanState {
         let mut request = ScanRequestPb::default();
         request.new_scan_request = Some(new_scan_request);
+        request.batch_size_bytes = self.batch_size_bytes;
 
-        let call =
-            TabletServerService::scan(Arc::new(request), Instant::now() + Duration::from_secs(60));
+        let call = TabletServerService::scan(Arc::new(request), Instant::now() + self.timeout);
         let rpc = ReplicaRpc::new(
-            tablet,
+            self.tablet.clone(),
             call,
-            Speculation::Staggered(Duration::from_millis(100)),
-            Selection::Closest,
+            self.speculation,
+            self.selection,
             Backoff::default(),
         );
-        TabletScan::New {
-            projected_schema,
-            rpc,
-        }
+        TabletScanState::New(rpc)
     }
 
     fn cont(
-        projected_schema: Schema,
+        &self,
         scanner_id: ScannerId,
         call_seq_id: u32,
         proxy: Proxy,
-    ) -> TabletScan {
+        replica: usize,
+    ) -> TabletScanState {
         let mut request = ScanRequestPb::default();
         request.scanner_id = Some(scanner_id.to_string().into_bytes());
         request.call_seq_id = Some(call_seq_id);
+        request.batch_size_bytes = self.batch_size_bytes;
 
-        let call =
-            TabletServerService::scan(Arc::new(request), Instant::now() + Duration::from_secs(60));
+        let call = TabletServerService::scan(Arc::new(request), Instant::now() + self.timeout);
 
         let rpc = ReplicaRpc::new(
-            proxy,
+            proxy.clone(),
             call,
             Speculation::Full,
             Selection::Closest,
             Backoff::default(),
         );
-        TabletScan::Continue {
-            projected_schema,
+        TabletScanState::Continue {
             scanner_id,
             call_seq_id,
+            proxy,
+            replica,
             rpc,
         }
     }
+
+    /// Returns an RPC which closes the tablet server scanner, if it is open.
+    fn close(&mut self) -> Option<ReplicaRpc<Proxy, ScanRequestPb, ScanResponsePb>> {
+        self.keep_alive.take();
+        match mem::replace(&mut self.state, TabletScanState::Finished) {
+            TabletScanState::Continue {
+                scanner_id,
+                call_seq_id,
+                proxy,
+                ..
+            } => {
+                let mut request = ScanRequestPb::default();
+                request.scanner_id = Some(scanner_id.to_string().into_bytes());
+                request.call_seq_id = Some(call_seq_id);
+                request.batch_size_bytes = Some(0);
+                request.close_scanner = Some(true);
+
+                let call =
+                    TabletServerService::scan(Arc::new(request), Instant::now() + self.timeout);
+                Some(ReplicaRpc::new(
+                    proxy,
+                    call,
+                    Speculation::Full,
+                    Selection::Closest,
+                    Backoff::default(),
+                ))
+            }
+            _ => None,
+        }
+    }
+
+    /// Keeps the scanner alive while the scan is not polled. A single task is spawned for each
+    /// scanner, and its delay is reset every time the scanner is used.
+    fn keep_alive(&mut self) {
+        let (scanner_id, proxy) = match self.state {
+            TabletScanState::Continue {
+                scanner_id,
+                ref proxy,
+                ..
+            } => (scanner_id, proxy),
+            _ => {
+                self.keep_alive = None;
+                return;
+            }
+        };
+
+        if let Some(ref keep_alive) = self.keep_alive {
+            if keep_alive.scanner_id == scanner_id {
+                *keep_alive.last_used.lock() = Instant::now();
+                return;
+            }
+        }
+
+        let (sender, receiver) = oneshot::channel();
+        let last_used = Arc::new(Mutex::new(Instant::now()));
+        let task = KeepAlive {
+            scanner_id,
+            proxy: proxy.clone(),
+            interval: self.keep_alive_interval,
+            timeout: self.timeout,
+            last_used: last_used.clone(),
+            delay: Delay::new(Instant::now() + self.keep_alive_interval),
+            rpc: None,
+            cancel: receiver,
+        };
+        // Keep-alives are best effort, so failing to spawn the task is not an error.
+        self.keep_alive = match DefaultExecutor::current().spawn(Box::new(task)) {
+            Ok(()) => Some(KeepAliveHandle {
+                scanner_id,
+                last_used,
+                _cancel: sender,
+            }),
+            Err(_) => None,
+        };
+    }
+
+    /// Reopens a fault tolerant scan after the last returned primary key, at the same snapshot.
+    /// The failed replica is marked stale, so that the scan is reopened on another replica.
+    fn resume(&self, failed_replica: usize) -> Option<TabletScanState> {
+        let mut request = self.resume_request.clone()?;
+        self.tablet.replicas[failed_replica].mark_stale();
+        request.read_mode = Some(ReadModePb::ReadAtSnapshot as i32);
+        request.snap_timestamp = self.snapshot_timestamp;
+        request.last_primary_key = self.last_primary_key.clone();
+        Some(self.open(request))
+    }
+
+    fn snapshot_timestamp(&self) -> Option<u64> {
+        self.snapshot_timestamp
+    }
 }
 
-impl Stream for TabletScan {
-    type Item = RowBatch;
-    type Error = Error;
+impl TabletScan {
+    /// Polls the tablet server for the next batch of rows.
+    fn poll_batch(&mut self) -> Poll<Option<RowBatch>, Error> {
+        let (proxy, mut response, sidecars) = loop {
+            let result = match self.state {
+                TabletScanState::New(ref mut rpc) => rpc.poll(),
+                TabletScanState::Continue { ref mut rpc, .. } => rpc.poll(),
+                TabletScanState::Finished => return Ok(Async::Ready(None)),
+            };
+
+            match result {
+                Ok(Async::Ready(item)) => break item,
+                Ok(Async::NotReady) => return Ok(Async::NotReady),
+                Err(error) => {
+                    let replica = match self.state {
+                        TabletScanState::Continue { replica, .. } => replica,
+                        _ => return Err(error),
+                    };
+                    if !is_replica_failure(&error) {
+                        return Err(error);
+                    }
+                    match self.resume(replica) {
+                        Some(state) => {
+                            warn!(
+                                "resuming scan of tablet {:?} after failure: {}",
+                                self.tablet.id(),
+                                error
+                            );
+                            self.state = state;
+                        }
+                        None => return Err(error),
+                    }
+                }
+            }
+        };
 
-    fn poll(&mut self) -> Poll<Option<RowBatch>, Error> {
-        trace!("TabletScan::poll");
-        match self {
-            TabletScan::New {
-                projected_schema,
-                rpc,
-            } => {
-                let (proxy, mut response, sidecars) = try_ready!(rpc.poll());
-                let batch = RowBatch::new(
-                    projected_schema.clone(),
-                    &response.data.take().unwrap_or_default(),
-                    sidecars,
-                )?;
-                *self = if response.has_more_results() {
+        if let Some(timestamp) = response.propagated_timestamp {
+            self.client.observe_timestamp(timestamp);
+        }
+        if response.snap_timestamp.is_some() {
+            self.snapshot_timestamp = response.snap_timestamp;
+        }
+        if response.last_primary_key.is_some() {
+            self.last_primary_key = response.last_primary_key.take();
+        }
+
+        let batch = RowBatch::new(
+            self.projected_schema.clone(),
+            &response.data.take().unwrap_or_default(),
+            sidecars,
+        )?;
+
+        self.state = if !response.has_more_results() {
+            TabletScanState::Finished
+        } else {
+            match self.state {
+                TabletScanState::Continue {
+                    scanner_id,
+                    call_seq_id,
+                    replica,
+                    ..
+                } => self.cont(scanner_id, call_seq_id + 1, proxy, replica),
+                TabletScanState::New(ref rpc) => {
                     let scanner_id = ScannerId::parse_bytes(
                         &response
                             .scanner_id
                             .expect_field("ScanResponsePb", "scanner_id")?,
                     )?;
-                    // NLL hack: these schema clones are nasty.
-                    TabletScan::cont(projected_schema.clone(), scanner_id, 1, proxy)
-                } else {
-                    TabletScan::Finished
-                };
+                    let replica = rpc.responder().expect("scan RPC has not completed");
+                    self.cont(scanner_id, 1, proxy, replica)
+                }
+                TabletScanState::Finished => unreachable!(),
+            }
+        };
 
-                Ok(Async::Ready(Some(batch)))
+        Ok(Async::Ready(Some(batch)))
+    }
+}
+
+impl Stream for TabletScan {
+    type Item = RowBatch;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Option<RowBatch>, Error> {
+        trace!("TabletScan::poll");
+        // The scan is being polled, so the scanner is in use.
+        if let Some(ref keep_alive) = self.keep_alive {
+            *keep_alive.last_used.lock() = Instant::now();
+        }
+
+        // Fetch the next batch, and up to `prefetch` batches ahead of it.
+        while self.buffer.len() <= self.prefetch {
+            match self.poll_batch()? {
+                Async::Ready(Some(batch)) => self.buffer.push_back(batch),
+                Async::Ready(None) | Async::NotReady => break,
             }
-            TabletScan::Continue {
-                projected_schema,
-                scanner_id,
-                call_seq_id,
-                rpc,
-            } => {
-                let (proxy, mut response, sidecars) = try_ready!(rpc.poll());
-                let batch = RowBatch::new(
-                    projected_schema.clone(),
-                    &response.data.take().unwrap_or_default(),
-                    sidecars,
-                )?;
-
-                *self = if response.has_more_results() {
-                    TabletScan::cont(
-                        projected_schema.clone(),
-                        *scanner_id,
-                        *call_seq_id + 1,
-                        proxy,
-                    )
-                } else {
-                    TabletScan::Finished
-                };
+        }
 
+        match self.buffer.pop_front() {
+            Some(batch) => {
+                self.keep_alive();
                 Ok(Async::Ready(Some(batch)))
             }
-            TabletScan::Finished => Ok(Async::Ready(None)),
+            None => match self.state {
+                TabletScanState::Finished => Ok(Async::Ready(None)),
+                _ => Ok(Async::NotReady),
+            },
+        }
+    }
+}
+
+impl Drop for TabletScan {
+    fn drop(&mut self) {
+        if let Some(rpc) = self.close() {
+            // The RPC is canceled if it is dropped, so it must be driven by a separate task.
+            let task = Box::new(rpc.map(|_| ()).map_err(|_| ()));
+            if let Err(error) = DefaultExecutor::current().spawn(task) {
+                warn!(
+                    "failed to close scanner of tablet {:?}: {:?}",
+                    self.tablet.id(),
+                    error
+                );
+            }
+        }
+    }
+}
+
+/// A handle to the keep-alive task of a scanner. The task is canceled when the handle is dropped.
+struct KeepAliveHandle {
+    scanner_id: ScannerId,
+    /// The last time the scanner was used by the scan.
+    last_used: Arc<Mutex<Instant>>,
+    _cancel: oneshot::Sender<()>,
+}
+
+/// A background task which periodically sends keep-alive requests for an idle scanner. The task
+/// completes when the cancellation sender is dropped, or if a keep-alive request fails.
+struct KeepAlive {
+    scanner_id: ScannerId,
+    proxy: Proxy,
+    interval: Duration,
+    timeout: Duration,
+    /// The last time the scanner was used by the scan.
+    last_used: Arc<Mutex<Instant>>,
+    delay: Delay,
+    rpc: Option<ReplicaRpc<Proxy, ScannerKeepAliveRequestPb, ScannerKeepAliveResponsePb>>,
+    cancel: oneshot::Receiver<()>,
+}
+
+impl Future for KeepAlive {
+    type Item = ();
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<(), ()> {
+        match self.cancel.poll() {
+            Ok(Async::NotReady) => (),
+            _ => return Ok(Async::Ready(())),
+        }
+
+        loop {
+            if let Some(ref mut rpc) = self.rpc {
+                match rpc.poll() {
+                    Ok(Async::Ready(_)) => (),
+                    Ok(Async::NotReady) => return Ok(Async::NotReady),
+                    Err(error) => {
+                        warn!(
+                            "failed to keep scanner {:?} alive: {}",
+                            self.scanner_id, error
+                        );
+                        return Ok(Async::Ready(()));
+                    }
+                }
+            }
+            if self.rpc.take().is_some() {
+                self.delay.reset(Instant::now() + self.interval);
+            }
+
+            match self.delay.poll() {
+                Ok(Async::Ready(())) => {
+                    // The scanner has been used since the delay was set, so it is not yet idle.
+                    let idle_deadline = *self.last_used.lock() + self.interval;
+                    if idle_deadline > Instant::now() {
+                        self.delay.reset(idle_deadline);
+                        continue;
+                    }
+                    trace!("keeping scanner {:?} alive", self.scanner_id);
+                    let request = ScannerKeepAliveRequestPb {
+                        scanner_id: self.scanner_id.to_string().into_bytes(),
+                    };
+                    let call = TabletServerService::scanner_keep_alive(
+                        Arc::new(request),
+                        Instant::now() + self.timeout,
+                    );
+                    self.rpc = Some(ReplicaRpc::new(
+                        self.proxy.clone(),
+                        call,
+                        Speculation::Full,
+                        Selection::Closest,
+                        Backoff::default(),
+                    ));
+                }
+                Ok(Async::NotReady) => return Ok(Async::NotReady),
+                Err(_) => return Ok(Async::Ready(())),
+            }
         }
     }
 }
@@ -528,11 +2023,15 @@ impl Stream for TabletScan {
 mod test {
 
     use super::*;
-    use mini_cluster::MiniCluster;
+    use mini_cluster::{
+        create_table, create_table_with_rows, insert_rows, MiniCluster, MiniClusterConfig,
+    };
+    use value::write_var_len_value;
     use Client;
     use Column;
     use DataType;
     use Options;
+    use RaftRole;
     use SchemaBuilder;
     use TableBuilder;
     use WriterConfig;
@@ -541,6 +2040,16 @@ mod test {
     use futures::future;
     use tokio::runtime::current_thread::Runtime;
 
+    fn count_rows(runtime: &mut Runtime, builder: ScanBuilder) -> usize {
+        let scan = runtime
+            .block_on(future::lazy::<_, Result<Scan>>(|| Ok(builder.build())))
+            .unwrap();
+        let batches: Vec<RowBatch> = runtime
+            .block_on(future::lazy(|| scan.collect()))
+            .unwrap();
+        batches.into_iter().map(|batch| batch.num_rows()).sum()
+    }
+
     #[test]
     fn count() {
         let _ = env_logger::try_init();
@@ -551,44 +2060,16 @@ mod test {
             .block_on(Client::new(cluster.master_addrs(), Options::default()))
             .expect("client");
 
-        let schema = SchemaBuilder::new()
-            .add_column(Column::new("key", DataType::Int32).set_not_null())
-            .add_column(Column::new("val", DataType::Int32))
-            .set_primary_key(vec!["key"])
-            .build()
-            .unwrap();
-
-        let mut table_builder = TableBuilder::new("count", schema.clone());
-        table_builder.add_hash_partitions(vec!["key"], 4);
-        table_builder.set_num_replicas(1);
-
-        // TODO: don't wait for table creation in order to test retry logic.
-        let table_id = runtime
-            .block_on(client.create_table(table_builder))
-            .unwrap();
-
-        let table = runtime.block_on(client.open_table_by_id(table_id)).unwrap();
-
-        let mut writer = table.new_writer(WriterConfig::default());
-
         let num_rows = 100i32;
-
-        // TODO: remove lazy once apply no longer polls.
-        runtime
-            .block_on(future::lazy::<_, Result<()>>(|| {
-                // Insert a bunch of values
-                for i in 0..num_rows {
-                    let mut insert = table.schema().new_row();
-                    insert.set("key", i).unwrap();
-                    insert.set("val", i).unwrap();
-                    writer.insert(insert);
-                }
-                Ok(())
-            })).unwrap();
-
-        runtime
-            .block_on(future::poll_fn(|| writer.poll_flush()))
-            .unwrap();
+        let table = create_table_with_rows(
+            &mut runtime,
+            &mut client,
+            "count",
+            |builder| {
+                builder.add_hash_partitions(vec!["key"], 4);
+            },
+            num_rows,
+        );
 
         let scan: Scan = runtime
             .block_on(::futures::future::lazy::<_, Result<Scan>>(|| {
@@ -615,40 +2096,16 @@ mod test {
             .block_on(Client::new(cluster.master_addrs(), Options::default()))
             .expect("client");
 
-        let schema = SchemaBuilder::new()
-            .add_column(Column::new("key", DataType::Int32).set_not_null())
-            .add_column(Column::new("val", DataType::Int32))
-            .set_primary_key(vec!["key"])
-            .build()
-            .unwrap();
-
-        let mut table_builder = TableBuilder::new("count", schema.clone());
-        table_builder.add_hash_partitions(vec!["key"], 4);
-        table_builder.set_num_replicas(1);
-
-        // TODO: don't wait for table creation in order to test retry logic.
-        let table_id = runtime
-            .block_on(client.create_table(table_builder))
-            .unwrap();
-        let table = runtime.block_on(client.open_table_by_id(table_id)).unwrap();
-        let mut writer = table.new_writer(WriterConfig::default());
         let num_rows = 10i32;
-
-        // TODO: remove lazy once apply no longer polls.
-        runtime
-            .block_on(future::lazy::<_, Result<()>>(|| {
-                // Insert a bunch of values
-                for i in 0..num_rows {
-                    let mut insert = table.schema().new_row();
-                    insert.set("key", i).unwrap();
-                    insert.set("val", i).unwrap();
-                    writer.insert(insert);
-                }
-                Ok(())
-            })).unwrap();
-        runtime
-            .block_on(future::poll_fn(|| writer.poll_flush()))
-            .unwrap();
+        let table = create_table_with_rows(
+            &mut runtime,
+            &mut client,
+            "count",
+            |builder| {
+                builder.add_hash_partitions(vec!["key"], 4);
+            },
+            num_rows,
+        );
 
         let scan: Scan = runtime
             .block_on(::futures::future::lazy::<_, Result<Scan>>(|| {
@@ -686,40 +2143,16 @@ mod test {
             .block_on(Client::new(cluster.master_addrs(), Options::default()))
             .expect("client");
 
-        let schema = SchemaBuilder::new()
-            .add_column(Column::new("key", DataType::Int32).set_not_null())
-            .add_column(Column::new("val", DataType::Int32))
-            .set_primary_key(vec!["key"])
-            .build()
-            .unwrap();
-
-        let mut table_builder = TableBuilder::new("count", schema.clone());
-        table_builder.add_hash_partitions(vec!["key"], 4);
-        table_builder.set_num_replicas(1);
-
-        // TODO: don't wait for table creation in order to test retry logic.
-        let table_id = runtime
-            .block_on(client.create_table(table_builder))
-            .unwrap();
-        let table = runtime.block_on(client.open_table_by_id(table_id)).unwrap();
-        let mut writer = table.new_writer(WriterConfig::default());
         let num_rows = 10i32;
-
-        // TODO: remove lazy once apply no longer polls.
-        runtime
-            .block_on(future::lazy::<_, Result<()>>(|| {
-                // Insert a bunch of values
-                for i in 0..num_rows {
-                    let mut insert = table.schema().new_row();
-                    insert.set("key", i).unwrap();
-                    insert.set("val", i).unwrap();
-                    writer.insert(insert);
-                }
-                Ok(())
-            })).unwrap();
-        runtime
-            .block_on(future::poll_fn(|| writer.poll_flush()))
-            .unwrap();
+        let table = create_table_with_rows(
+            &mut runtime,
+            &mut client,
+            "count",
+            |builder| {
+                builder.add_hash_partitions(vec!["key"], 4);
+            },
+            num_rows,
+        );
 
         let scan: Scan = runtime
             .block_on(::futures::future::lazy::<_, Result<Scan>>(|| {
@@ -752,4 +2185,932 @@ mod test {
 
         assert_eq!(rows, expected);
     }
+
+    #[test]
+    fn read_modes() {
+        let _ = env_logger::try_init();
+        let mut cluster = MiniCluster::default();
+        let mut runtime = Runtime::new().unwrap();
+
+        let mut client = runtime
+            .block_on(Client::new(cluster.master_addrs(), Options::default()))
+            .expect("client");
+
+        let num_rows = 10i32;
+        let table = create_table_with_rows(
+            &mut runtime,
+            &mut client,
+            "read_modes",
+            |builder| {
+                builder.add_hash_partitions(vec!["key"], 4);
+            },
+            0,
+        );
+
+        let before_insert = SystemTime::now();
+        insert_rows(&mut runtime, &table, num_rows, |row, i| {
+            row.set("key", i).unwrap();
+            row.set("val", i).unwrap();
+        });
+
+        assert_eq!(
+            num_rows as usize,
+            count_rows(
+                &mut runtime,
+                table.scan_builder().read_mode(ReadMode::Snapshot(None))
+            )
+        );
+        assert_eq!(
+            num_rows as usize,
+            count_rows(
+                &mut runtime,
+                table.scan_builder().read_mode(ReadMode::ReadYourWrites)
+            )
+        );
+        assert_eq!(
+            0,
+            count_rows(
+                &mut runtime,
+                table
+                    .scan_builder()
+                    .read_mode(ReadMode::snapshot_at(before_insert))
+            )
+        );
+    }
+
+    #[test]
+    fn fault_tolerant() {
+        let _ = env_logger::try_init();
+        let mut cluster = MiniCluster::new(MiniClusterConfig::default().num_tservers(3));
+        let mut runtime = Runtime::new().unwrap();
+
+        let mut client = runtime
+            .block_on(Client::new(cluster.master_addrs(), Options::default()))
+            .expect("client");
+
+        let schema = SchemaBuilder::new()
+            .add_column(Column::new("key", DataType::Int32).set_not_null())
+            .add_column(Column::new("val", DataType::String))
+            .set_primary_key(vec!["key"])
+            .build()
+            .unwrap();
+
+        let mut table_builder = TableBuilder::new("fault_tolerant", schema.clone());
+        table_builder.set_range_partition_columns(vec!["key"]);
+        table_builder.set_num_replicas(3);
+
+        let table = create_table(&mut runtime, &mut client, table_builder);
+
+        // Write enough data that the tablet is returned in multiple batches.
+        let num_rows = 20_000i32;
+        let val = "x".repeat(200);
+        insert_rows(&mut runtime, &table, num_rows, |row, i| {
+            row.set("key", i).unwrap();
+            row.set("val", &val[..]).unwrap();
+        });
+
+        // Find the tablet server hosting the leader replica, which the scanner is opened on.
+        let tablets = runtime.block_on(table.tablets().collect()).unwrap();
+        assert_eq!(1, tablets.len());
+        let leader = tablets[0]
+            .replicas()
+            .iter()
+            .find(|replica| replica.role() == RaftRole::Leader)
+            .expect("leader replica")
+            .rpc_addrs()[0]
+            .clone();
+        let leader_index = cluster
+            .tserver_addrs()
+            .iter()
+            .position(|addr| *addr == leader)
+            .expect("leader tablet server");
+
+        let scan = runtime
+            .block_on(future::lazy::<_, Result<Scan>>(|| {
+                Ok(table
+                    .scan_builder()
+                    .select(vec!["key"])?
+                    .replica_selection(Selection::Leader)
+                    .batch_size_bytes(16 * 1024)
+                    .fault_tolerant()
+                    .build())
+            })).unwrap();
+
+        let (batch, scan) = runtime
+            .block_on(future::lazy(|| scan.into_future()))
+            .map_err(|(error, _)| error)
+            .unwrap();
+        let mut keys = batch
+            .unwrap()
+            .into_iter()
+            .map(|row| row.get::<_, i32>("key").unwrap())
+            .collect::<Vec<_>>();
+
+        assert!(!keys.is_empty() && keys.len() < num_rows as usize);
+
+        // Kill the tablet server hosting the scanner mid-scan. The scan must resume on another
+        // replica.
+        cluster.stop_tserver(leader_index as u32);
+
+        let batches = runtime
+            .block_on(future::lazy(|| scan.collect()))
+            .unwrap();
+        for batch in batches {
+            keys.extend(batch.into_iter().map(|row| row.get::<_, i32>("key").unwrap()));
+        }
+
+        keys.sort();
+        assert_eq!(keys, (0..num_rows).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn primary_key_bounds() {
+        let _ = env_logger::try_init();
+        let mut cluster = MiniCluster::default();
+        let mut runtime = Runtime::new().unwrap();
+
+        let mut client = runtime
+            .block_on(Client::new(cluster.master_addrs(), Options::default()))
+            .expect("client");
+
+        let num_rows = 100i32;
+        let table = create_table_with_rows(
+            &mut runtime,
+            &mut client,
+            "primary_key_bounds",
+            |builder| {
+                builder.set_range_partition_columns(vec!["key"]);
+                for split in &[25, 50, 75] {
+                    let mut row = builder.schema().new_row();
+                    row.set(0, *split).unwrap();
+                    builder.add_range_partition_split(row);
+                }
+            },
+            num_rows,
+        );
+
+        let key = |key: i32| {
+            let mut row = table.schema().new_row();
+            row.set(0, key).unwrap();
+            row
+        };
+
+        let bounded = |lower: RangePartitionBound, upper: RangePartitionBound| {
+            table
+                .scan_builder()
+                .primary_key_lower_bound(lower)
+                .unwrap()
+                .primary_key_upper_bound(upper)
+                .unwrap()
+        };
+
+        assert_eq!(
+            40,
+            count_rows(
+                &mut runtime,
+                bounded(
+                    RangePartitionBound::Inclusive(key(30)),
+                    RangePartitionBound::Exclusive(key(70))
+                )
+            )
+        );
+        assert_eq!(
+            40,
+            count_rows(
+                &mut runtime,
+                bounded(
+                    RangePartitionBound::Exclusive(key(29)),
+                    RangePartitionBound::Inclusive(key(69))
+                )
+            )
+        );
+        assert_eq!(
+            0,
+            count_rows(
+                &mut runtime,
+                bounded(
+                    RangePartitionBound::Inclusive(key(50)),
+                    RangePartitionBound::Exclusive(key(50))
+                )
+            )
+        );
+        assert_eq!(
+            75,
+            count_rows(
+                &mut runtime,
+                table
+                    .scan_builder()
+                    .primary_key_lower_bound(RangePartitionBound::Inclusive(key(25)))
+                    .unwrap()
+            )
+        );
+
+        // Bounds must set every primary key column.
+        assert!(
+            table
+                .scan_builder()
+                .primary_key_lower_bound(RangePartitionBound::Inclusive(table.schema().new_row()))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn scan_tokens() {
+        let _ = env_logger::try_init();
+        let mut cluster = MiniCluster::default();
+        let mut runtime = Runtime::new().unwrap();
+
+        let mut client = runtime
+            .block_on(Client::new(cluster.master_addrs(), Options::default()))
+            .expect("client");
+
+        let num_rows = 100i32;
+        let table = create_table_with_rows(
+            &mut runtime,
+            &mut client,
+            "scan_tokens",
+            |builder| {
+                builder.add_hash_partitions(vec!["key"], 4);
+            },
+            num_rows,
+        );
+
+        let tokens = runtime
+            .block_on(
+                table
+                    .scan_builder()
+                    .select(vec!["val"])
+                    .unwrap()
+                    .filter("val", Filter::range(10i32..))
+                    .unwrap()
+                    .build_tokens(),
+            ).unwrap();
+        assert_eq!(4, tokens.len());
+
+        let mut vals = Vec::new();
+        for token in tokens {
+            let scan = runtime
+                .block_on(ScanToken::deserialize_into_scan(
+                    &client,
+                    &token.serialize(),
+                )).unwrap();
+            assert_eq!(1, scan.projected_schema().columns().len());
+            let batches: Vec<RowBatch> = runtime
+                .block_on(future::lazy(|| scan.collect()))
+                .unwrap();
+            for batch in batches {
+                vals.extend(batch.into_iter().map(|row| row.get::<_, i32>("val").unwrap()));
+            }
+        }
+        vals.sort();
+        assert_eq!(vals, (10..num_rows).collect::<Vec<_>>());
+
+        // Tablets which can not hold matching rows are pruned.
+        let tokens = runtime
+            .block_on(
+                table
+                    .scan_builder()
+                    .filter("key", Filter::equals(42i32))
+                    .unwrap()
+                    .build_tokens(),
+            ).unwrap();
+        assert_eq!(1, tokens.len());
+        let scan = runtime
+            .block_on(tokens[0].clone().into_scan(&client))
+            .unwrap();
+        let batches: Vec<RowBatch> = runtime
+            .block_on(future::lazy(|| scan.collect()))
+            .unwrap();
+        let keys = batches
+            .into_iter()
+            .flat_map(|batch| {
+                batch
+                    .into_iter()
+                    .map(|row| row.get::<_, i32>("key").unwrap())
+                    .collect::<Vec<_>>()
+            }).collect::<Vec<_>>();
+        assert_eq!(vec![42], keys);
+
+        // Primary key bounds are carried by the tokens, and the execution options are set on the
+        // rehydrated scan builders.
+        let mut lower_bound = table.schema().new_row();
+        lower_bound.set("key", 50i32).unwrap();
+        let tokens = runtime
+            .block_on(
+                table
+                    .scan_builder()
+                    .primary_key_lower_bound(RangePartitionBound::Inclusive(lower_bound))
+                    .unwrap()
+                    .build_tokens(),
+            ).unwrap();
+        let mut keys = Vec::new();
+        for token in tokens {
+            let builder = runtime
+                .block_on(ScanToken::deserialize_into_scan_builder(
+                    &client,
+                    &token.serialize(),
+                )).unwrap();
+            let scan = runtime
+                .block_on(future::lazy::<_, Result<Scan>>(|| {
+                    Ok(builder.ordered().batch_size_bytes(16).build())
+                })).unwrap();
+            let batches: Vec<RowBatch> = runtime
+                .block_on(future::lazy(|| scan.collect()))
+                .unwrap();
+            let tablet_keys = batches
+                .into_iter()
+                .flat_map(|batch| {
+                    batch
+                        .into_iter()
+                        .map(|row| row.get::<_, i32>("key").unwrap())
+                        .collect::<Vec<_>>()
+                }).collect::<Vec<_>>();
+            assert!(tablet_keys.windows(2).all(|pair| pair[0] < pair[1]));
+            keys.extend(tablet_keys);
+        }
+        keys.sort();
+        assert_eq!(keys, (50..num_rows).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn limit() {
+        let _ = env_logger::try_init();
+        let mut cluster = MiniCluster::default();
+        let mut runtime = Runtime::new().unwrap();
+
+        let mut client = runtime
+            .block_on(Client::new(cluster.master_addrs(), Options::default()))
+            .expect("client");
+
+        let num_rows = 100i32;
+        let table = create_table_with_rows(
+            &mut runtime,
+            &mut client,
+            "limit",
+            |builder| {
+                builder.add_hash_partitions(vec!["key"], 4);
+            },
+            num_rows,
+        );
+
+        assert_eq!(10, count_rows(&mut runtime, table.scan_builder().limit(10)));
+        assert_eq!(
+            10,
+            count_rows(&mut runtime, table.scan_builder().count().limit(10))
+        );
+        assert_eq!(0, count_rows(&mut runtime, table.scan_builder().limit(0)));
+        assert_eq!(
+            num_rows as usize,
+            count_rows(&mut runtime, table.scan_builder().limit(1000))
+        );
+
+        // Small batches return each tablet in multiple batches.
+        let scan = runtime
+            .block_on(future::lazy::<_, Result<Scan>>(|| {
+                Ok(table
+                    .scan_builder()
+                    .batch_size_bytes(16)
+                    .timeout(Duration::from_secs(10))
+                    .build())
+            })).unwrap();
+        let batches: Vec<RowBatch> = runtime
+            .block_on(future::lazy(|| scan.collect()))
+            .unwrap();
+        assert!(batches.len() > 4);
+        assert_eq!(
+            num_rows as usize,
+            batches.into_iter().map(|batch| batch.num_rows()).sum()
+        );
+    }
+
+    #[test]
+    fn keep_alive() {
+        let _ = env_logger::try_init();
+        let mut cluster = MiniCluster::new(MiniClusterConfig::default().scanner_ttl(1000));
+        let mut runtime = Runtime::new().unwrap();
+
+        let mut client = runtime
+            .block_on(Client::new(cluster.master_addrs(), Options::default()))
+            .expect("client");
+
+        let num_rows = 100i32;
+        let table = create_table_with_rows(
+            &mut runtime,
+            &mut client,
+            "keep_alive",
+            |_| (),
+            num_rows,
+        );
+
+        let builder = |keep_alive_interval| {
+            table
+                .scan_builder()
+                .batch_size_bytes(16)
+                .keep_alive_interval(keep_alive_interval)
+        };
+        let stall = |runtime: &mut Runtime| {
+            runtime
+                .block_on(Delay::new(Instant::now() + Duration::from_secs(3)))
+                .unwrap();
+        };
+
+        // Stall the scan for longer than the scanner TTL. The scanner is kept alive in the
+        // background.
+        let scan = runtime
+            .block_on(future::lazy::<_, Result<Scan>>(|| {
+                Ok(builder(Duration::from_millis(200)).build())
+            })).unwrap();
+        let (batch, scan) = runtime
+            .block_on(future::lazy(|| scan.into_future()))
+            .map_err(|(error, _)| error)
+            .unwrap();
+        let mut count = batch.unwrap().num_rows();
+        stall(&mut runtime);
+        let batches: Vec<RowBatch> = runtime
+            .block_on(future::lazy(|| scan.collect()))
+            .unwrap();
+        count += batches.into_iter().map(|batch| batch.num_rows()).sum::<usize>();
+        assert_eq!(num_rows as usize, count);
+
+        // Without keep-alives the scanner expires.
+        let scan = runtime
+            .block_on(future::lazy::<_, Result<Scan>>(|| {
+                Ok(builder(Duration::from_secs(60)).build())
+            })).unwrap();
+        let (_, scan) = runtime
+            .block_on(future::lazy(|| scan.into_future()))
+            .map_err(|(error, _)| error)
+            .unwrap();
+        stall(&mut runtime);
+        assert!(
+            runtime
+                .block_on(future::lazy(|| scan.collect()))
+                .is_err()
+        );
+
+        // Closing the scan releases the scanner.
+        let scan = runtime
+            .block_on(future::lazy::<_, Result<Scan>>(|| {
+                Ok(builder(Duration::from_secs(60)).build())
+            })).unwrap();
+        let (_, scan) = runtime
+            .block_on(future::lazy(|| scan.into_future()))
+            .map_err(|(error, _)| error)
+            .unwrap();
+        runtime.block_on(future::lazy(|| scan.close())).unwrap();
+    }
+
+    #[test]
+    fn ordered() {
+        let _ = env_logger::try_init();
+        let mut cluster = MiniCluster::default();
+        let mut runtime = Runtime::new().unwrap();
+
+        let mut client = runtime
+            .block_on(Client::new(cluster.master_addrs(), Options::default()))
+            .expect("client");
+
+        let schema = SchemaBuilder::new()
+            .add_column(Column::new("key", DataType::Int32).set_not_null())
+            .add_column(Column::new("val", DataType::String))
+            .set_primary_key(vec!["key"])
+            .build()
+            .unwrap();
+
+        let mut table_builder = TableBuilder::new("ordered", schema.clone());
+        table_builder.add_hash_partitions(vec!["key"], 4);
+        table_builder.set_num_replicas(1);
+
+        let table = create_table(&mut runtime, &mut client, table_builder);
+        let num_rows = 1000i32;
+        insert_rows(&mut runtime, &table, num_rows, |row, i| {
+            row.set("key", i).unwrap();
+            row.set("val", i.to_string()).unwrap();
+        });
+
+        // The primary key column is appended to the projection.
+        let scan = runtime
+            .block_on(future::lazy::<_, Result<Scan>>(|| {
+                Ok(table
+                    .scan_builder()
+                    .select(vec!["val"])?
+                    .ordered()
+                    .batch_size_bytes(256)
+                    .build())
+            })).unwrap();
+        assert_eq!(2, scan.projected_schema().columns().len());
+        match scan.read_mode() {
+            ReadMode::Snapshot(_) => (),
+            read_mode => panic!("unexpected read mode: {:?}", read_mode),
+        }
+
+        let batches: Vec<RowBatch> = runtime
+            .block_on(future::lazy(|| scan.collect()))
+            .unwrap();
+        let mut rows = Vec::new();
+        for batch in batches {
+            for row in batch.into_iter() {
+                rows.push((
+                    row.get::<_, i32>("key").unwrap(),
+                    row.get::<_, String>("val").unwrap(),
+                ));
+            }
+        }
+        assert_eq!(
+            (0..num_rows).map(|i| (i, i.to_string())).collect::<Vec<_>>(),
+            rows
+        );
+
+        assert_eq!(
+            10,
+            count_rows(&mut runtime, table.scan_builder().ordered().limit(10))
+        );
+    }
+
+    #[test]
+    fn parallel() {
+        let _ = env_logger::try_init();
+        let mut cluster = MiniCluster::default();
+        let mut runtime = Runtime::new().unwrap();
+
+        let mut client = runtime
+            .block_on(Client::new(cluster.master_addrs(), Options::default()))
+            .expect("client");
+
+        let num_rows = 1000i32;
+        let table = create_table_with_rows(
+            &mut runtime,
+            &mut client,
+            "parallel",
+            |builder| {
+                builder.add_hash_partitions(vec!["key"], 4);
+            },
+            num_rows,
+        );
+
+        let builder = || table.scan_builder().batch_size_bytes(256);
+
+        assert_eq!(
+            num_rows as usize,
+            count_rows(&mut runtime, builder().parallel(4))
+        );
+        assert_eq!(
+            num_rows as usize,
+            count_rows(&mut runtime, builder().parallel(2).prefetch(2))
+        );
+        assert_eq!(
+            num_rows as usize,
+            count_rows(&mut runtime, builder().prefetch(4))
+        );
+        assert_eq!(
+            num_rows as usize,
+            count_rows(&mut runtime, builder().parallel(4).read_mode(ReadMode::Snapshot(None)))
+        );
+        assert_eq!(
+            10,
+            count_rows(&mut runtime, builder().parallel(4).prefetch(1).limit(10))
+        );
+    }
+
+    #[test]
+    fn tablets() {
+        let _ = env_logger::try_init();
+        let mut cluster = MiniCluster::default();
+        let mut runtime = Runtime::new().unwrap();
+
+        let mut client = runtime
+            .block_on(Client::new(cluster.master_addrs(), Options::default()))
+            .expect("client");
+
+        let num_rows = 1000i32;
+        let table = create_table_with_rows(
+            &mut runtime,
+            &mut client,
+            "tablets",
+            |builder| {
+                builder.add_hash_partitions(vec!["key"], 4);
+            },
+            num_rows,
+        );
+
+        let tablets = runtime.block_on(table.tablets().collect()).unwrap();
+        assert_eq!(4, tablets.len());
+
+        let mut by_partition = 0;
+        let mut by_id = 0;
+        for tablet in &tablets {
+            let count = count_rows(
+                &mut runtime,
+                table.scan_builder().partition(tablet.partition()),
+            );
+            assert!(count < num_rows as usize);
+            assert_eq!(
+                count,
+                count_rows(
+                    &mut runtime,
+                    table.scan_builder().tablets(vec![tablet.id()]),
+                )
+            );
+            by_partition += count;
+            by_id += count_rows(
+                &mut runtime,
+                table.scan_builder().ordered().tablets(vec![tablet.id()]),
+            );
+        }
+        assert_eq!(num_rows as usize, by_partition);
+        assert_eq!(num_rows as usize, by_id);
+
+        let tablet_ids = tablets.iter().map(|tablet| tablet.id()).collect::<Vec<_>>();
+        assert_eq!(
+            num_rows as usize,
+            count_rows(
+                &mut runtime,
+                table.scan_builder().parallel(2).tablets(tablet_ids)
+            )
+        );
+        assert_eq!(
+            0,
+            count_rows(&mut runtime, table.scan_builder().tablets(Vec::new()))
+        );
+    }
+
+    #[test]
+    fn replica_selection() {
+        let _ = env_logger::try_init();
+        let mut cluster = MiniCluster::new(MiniClusterConfig::default().num_tservers(3));
+        let mut runtime = Runtime::new().unwrap();
+
+        let mut client = runtime
+            .block_on(Client::new(cluster.master_addrs(), Options::default()))
+            .expect("client");
+
+        let num_rows = 1000i32;
+        let table = create_table_with_rows(
+            &mut runtime,
+            &mut client,
+            "replica_selection",
+            |builder| {
+                builder.add_hash_partitions(vec!["key"], 2);
+                builder.set_num_replicas(3);
+            },
+            num_rows,
+        );
+
+        let speculations = [
+            Speculation::None,
+            Speculation::Full,
+            Speculation::Staggered(Duration::from_millis(1)),
+        ];
+        for &selection in &[Selection::Leader, Selection::Closest, Selection::Any] {
+            for &speculation in &speculations {
+                let builder = table
+                    .scan_builder()
+                    .read_mode(ReadMode::Snapshot(None))
+                    .replica_selection(selection)
+                    .speculation(speculation);
+                assert_eq!(num_rows as usize, count_rows(&mut runtime, builder));
+            }
+        }
+    }
+
+    #[test]
+    fn shared_rows() {
+        let _ = env_logger::try_init();
+        let mut cluster = MiniCluster::default();
+        let mut runtime = Runtime::new().unwrap();
+
+        let mut client = runtime
+            .block_on(Client::new(cluster.master_addrs(), Options::default()))
+            .expect("client");
+
+        let schema = SchemaBuilder::new()
+            .add_column(Column::new("key", DataType::Int32).set_not_null())
+            .add_column(Column::new("val", DataType::String))
+            .set_primary_key(vec!["key"])
+            .build()
+            .unwrap();
+
+        let mut table_builder = TableBuilder::new("shared_rows", schema.clone());
+        table_builder.add_hash_partitions(vec!["key"], 2);
+        table_builder.set_num_replicas(1);
+
+        let table = create_table(&mut runtime, &mut client, table_builder);
+        let num_rows = 1000i32;
+        insert_rows(&mut runtime, &table, num_rows, |row, i| {
+            row.set("key", i).unwrap();
+            row.set("val", i.to_string()).unwrap();
+        });
+
+        let scan = runtime
+            .block_on(future::lazy::<_, Result<Scan>>(|| {
+                Ok(table.scan_builder().batch_size_bytes(256).build())
+            })).unwrap();
+        let batches: Vec<RowBatch> = runtime
+            .block_on(future::lazy(|| scan.collect()))
+            .unwrap();
+
+        // The rows outlive the batches.
+        let mut rows: Vec<Row<'static>> = batches.into_iter().flatten().collect();
+        rows.sort_by_key(|row| row.get::<_, i32>("key").unwrap());
+        assert_eq!(num_rows as usize, rows.len());
+        for (i, row) in rows.iter().enumerate() {
+            assert_eq!(i as i32, row.get::<_, i32>("key").unwrap());
+            assert_eq!(&i.to_string()[..], row.get::<_, &str>("val").unwrap());
+        }
+
+        // Shared rows can be cloned, modified and copied.
+        let mut row = rows[42].clone();
+        row.set("key", 1042).unwrap();
+        assert_eq!(1042, row.get::<_, i32>("key").unwrap());
+        assert_eq!("42", row.get::<_, &str>("val").unwrap());
+        let row = rows.swap_remove(7).into_owned();
+        drop(rows);
+        assert_eq!(7, row.get::<_, i32>("key").unwrap());
+        assert_eq!("7", row.get::<_, &str>("val").unwrap());
+    }
+
+    #[test]
+    fn columns() {
+        let _ = env_logger::try_init();
+        let mut cluster = MiniCluster::default();
+        let mut runtime = Runtime::new().unwrap();
+
+        let mut client = runtime
+            .block_on(Client::new(cluster.master_addrs(), Options::default()))
+            .expect("client");
+
+        let schema = SchemaBuilder::new()
+            .add_column(Column::new("key", DataType::Int32).set_not_null())
+            .add_column(Column::new("val", DataType::Int64).set_nullable())
+            .add_column(Column::new("name", DataType::String).set_nullable())
+            .set_primary_key(vec!["key"])
+            .build()
+            .unwrap();
+
+        let mut table_builder = TableBuilder::new("columns", schema.clone());
+        table_builder.set_range_partition_columns(vec!["key"]);
+        table_builder.set_num_replicas(1);
+
+        let table = create_table(&mut runtime, &mut client, table_builder);
+        let num_rows = 100i32;
+        insert_rows(&mut runtime, &table, num_rows, |row, i| {
+            row.set("key", i).unwrap();
+            if i % 3 != 0 {
+                row.set("val", i64::from(i) * 10).unwrap();
+                row.set("name", i.to_string()).unwrap();
+            }
+        });
+
+        let scan = runtime
+            .block_on(future::lazy::<_, Result<Scan>>(|| {
+                Ok(table.scan_builder().ordered().build())
+            })).unwrap();
+        let batches: Vec<RowBatch> = runtime
+            .block_on(future::lazy(|| scan.collect()))
+            .unwrap();
+
+        let mut keys = Vec::new();
+        let mut vals = Vec::new();
+        let mut names = Vec::new();
+        for batch in &batches {
+            keys.extend(batch.column::<i32, _>("key").unwrap());
+            vals.extend(batch.column::<i64, _>("val").unwrap());
+            names.extend(
+                batch
+                    .column::<&str, _>(2)
+                    .unwrap()
+                    .map(|name| name.map(str::to_owned)),
+            );
+
+            assert!(batch.column::<i64, _>("key").is_err());
+            assert!(batch.column::<Option<i64>, _>("val").is_err());
+            assert!(batch.column::<i64, _>("missing").is_err());
+        }
+
+        let mut expected_vals = Vec::new();
+        let mut expected_names = Vec::new();
+        for i in 0..num_rows {
+            if i % 3 == 0 {
+                expected_vals.push(None);
+                expected_names.push(None);
+            } else {
+                expected_vals.push(Some(i64::from(i) * 10));
+                expected_names.push(Some(i.to_string()));
+            }
+        }
+        assert_eq!((0..num_rows).map(Some).collect::<Vec<_>>(), keys);
+        assert_eq!(expected_vals, vals);
+        assert_eq!(expected_names, names);
+    }
+
+    #[test]
+    fn column_invalid_utf8() {
+        let schema = SchemaBuilder::new()
+            .add_column(Column::new("key", DataType::String).set_not_null())
+            .set_primary_key(vec!["key"])
+            .build()
+            .unwrap();
+
+        let indirect_data = Bytes::from(vec![0xff; 64]);
+        let mut data = BytesMut::from(vec![0; schema.row_len()]);
+        unsafe {
+            write_var_len_value(
+                data.as_mut_ptr(),
+                indirect_data.as_ptr(),
+                indirect_data.len(),
+                0,
+            );
+        }
+        let batch = RowBatch {
+            projected_schema: schema,
+            len: 1,
+            data: data.freeze(),
+            indirect_data: vec![indirect_data.clone()],
+        };
+
+        assert!(batch.column::<&str, _>("key").is_err());
+        assert!(batch.column::<String, _>(0).is_err());
+        assert_eq!(
+            vec![Some(&indirect_data[..])],
+            batch.column::<&[u8], _>("key").unwrap().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn decimal() {
+        let _ = env_logger::try_init();
+        let mut cluster = MiniCluster::default();
+        let mut runtime = Runtime::new().unwrap();
+
+        let mut client = runtime
+            .block_on(Client::new(cluster.master_addrs(), Options::default()))
+            .expect("client");
+
+        let schema = SchemaBuilder::new()
+            .add_column(
+                Column::new("key", DataType::Decimal64)
+                    .set_not_null()
+                    .set_precision(12)
+                    .set_scale(2),
+            ).add_column(Column::new("val32", DataType::Decimal32).set_scale(4))
+            .add_column(Column::new("val128", DataType::Decimal128).set_scale(10))
+            .set_primary_key(vec!["key"])
+            .build()
+            .unwrap();
+
+        let mut table_builder = TableBuilder::new("decimal", schema.clone());
+        table_builder.add_hash_partitions(vec!["key"], 4);
+        table_builder.set_num_replicas(1);
+
+        let table = create_table(&mut runtime, &mut client, table_builder);
+        assert_eq!(Some(12), table.schema().columns()[0].precision());
+        assert_eq!(Some(2), table.schema().columns()[0].scale());
+
+        let mut writer = table.new_writer(WriterConfig::default());
+        let num_rows = 10i64;
+
+        // TODO: remove lazy once apply no longer polls.
+        runtime
+            .block_on(future::lazy::<_, Result<()>>(|| {
+                for i in 0..num_rows {
+                    let mut insert = table.schema().new_row();
+                    insert.set("key", i * 100 + 1).unwrap();
+                    insert.set("val32", -(i as i32)).unwrap();
+                    insert.set("val128", i128::from(i) << 80).unwrap();
+                    writer.insert(insert);
+                }
+                Ok(())
+            })).unwrap();
+        runtime
+            .block_on(future::poll_fn(|| writer.poll_flush()))
+            .unwrap();
+
+        let scan: Scan = runtime
+            .block_on(future::lazy::<_, Result<Scan>>(|| {
+                Ok(table
+                    .scan_builder()
+                    .filter("key", Filter::range(501i64..))?
+                    .ordered()
+                    .build())
+            })).unwrap();
+        let batches: Vec<RowBatch> = runtime
+            .block_on(future::lazy(|| scan.collect()))
+            .unwrap();
+
+        let mut rows = Vec::new();
+        for batch in batches {
+            for row in batch.into_iter() {
+                rows.push((
+                    row.get::<_, i64>("key").unwrap(),
+                    row.get::<_, i32>("val32").unwrap(),
+                    row.get::<_, i128>("val128").unwrap(),
+                ));
+            }
+        }
+
+        let expected = (5..num_rows)
+            .map(|i| (i * 100 + 1, -(i as i32), i128::from(i) << 80))
+            .collect::<Vec<_>>();
+        assert_eq!(expected, rows);
+    }
 }
@@ -1,16 +1,20 @@
 use std::cmp;
 use std::collections::HashMap;
 use std::fmt;
+use std::str;
 use std::sync::Arc;
 
 use bitmap;
-use pb::{ColumnSchemaPb, SchemaPb};
+use pb::{ColumnSchemaPb, ColumnTypeAttributesPb, SchemaPb};
+use util;
 use CompressionType;
 use DataType;
 use EncodingType;
 use Error;
+use PhysicalType;
 use Result;
 use Row;
+use Value;
 
 /// `Column` instances hold schema and metadata about a column in a Kudu table.
 ///
@@ -24,6 +28,10 @@ pub struct Column {
     compression: CompressionType,
     encoding: EncodingType,
     cfile_block_size: u32,
+    precision: u8,
+    scale: u8,
+    /// The physical type and encoded value of the default value.
+    default: Option<(PhysicalType, Vec<u8>)>,
 }
 
 impl Column {
@@ -55,7 +63,55 @@ impl Column {
         }
     }
 
+    /// Returns the precision of a decimal column, or `None` if the column is not a decimal.
+    pub fn precision(&self) -> Option<u8> {
+        self.data_type
+            .decimal_precision_range()
+            .map(|_| self.precision)
+    }
+
+    /// Returns the scale of a decimal column, or `None` if the column is not a decimal.
+    pub fn scale(&self) -> Option<u8> {
+        self.data_type.decimal_precision_range().map(|_| self.scale)
+    }
+
+    /// Returns the default value of the column, or `None` if the column has no default value.
+    pub fn default_value<'a, V>(&'a self) -> Result<Option<V>>
+    where
+        V: Value<'a>,
+    {
+        if !V::can_read_from(self.data_type) {
+            return Err(Error::InvalidArgument(format!(
+                "type {:?} is invalid for column {:?}",
+                V::DATA_TYPE,
+                self
+            )));
+        }
+        let value = match self.default {
+            Some((_, ref value)) => value,
+            None => return Ok(None),
+        };
+        if self.data_type.is_var_len() {
+            if self.data_type == DataType::String {
+                str::from_utf8(value)?;
+            }
+        } else if value.len() != self.data_type.size() {
+            return Err(Error::Serialization(format!(
+                "invalid default value length {} for column {:?}",
+                value.len(),
+                self
+            )));
+        }
+        Ok(Some(unsafe { V::decode(value) }))
+    }
+
+    pub(crate) fn has_default_value(&self) -> bool {
+        self.default.is_some()
+    }
+
     /// Returns a new column.
+    ///
+    /// Decimal columns default to the maximum precision of the data type, and a scale of 0.
     pub fn new<S>(name: S, data_type: DataType) -> Column
     where
         S: Into<String>,
@@ -67,6 +123,9 @@ impl Column {
             compression: CompressionType::Default,
             encoding: EncodingType::Auto,
             cfile_block_size: 0,
+            precision: data_type.decimal_precision_range().map_or(0, |(_, max)| max),
+            scale: 0,
+            default: None,
         }
     }
 
@@ -100,7 +159,84 @@ impl Column {
         self
     }
 
+    /// Sets the precision of a decimal column, which is the total number of decimal digits.
+    ///
+    /// The precision must be within the range of the decimal data type: 1 to 9 for `Decimal32`,
+    /// 10 to 18 for `Decimal64`, and 19 to 38 for `Decimal128`.
+    pub fn set_precision(mut self, precision: u8) -> Column {
+        self.precision = precision;
+        self
+    }
+
+    /// Sets the scale of a decimal column, which is the number of decimal digits after the
+    /// decimal point. The scale must not exceed the precision.
+    pub fn set_scale(mut self, scale: u8) -> Column {
+        self.scale = scale;
+        self
+    }
+
+    /// Sets the default value of the column. The default is written to rows which are inserted
+    /// without a value for the column, and is read from existing rows when the column is added to
+    /// a table.
+    ///
+    /// A null value clears the default. The value type is checked against the column data type
+    /// when the schema is built, or when the column is added to a table.
+    pub fn set_default_value<'data, V>(mut self, value: V) -> Column
+    where
+        V: Value<'data>,
+    {
+        self.default = encode_default_value(value);
+        self
+    }
+
+    /// Checks that the precision and scale of a decimal column, and the type of the default value,
+    /// are valid for the data type. Decimal default values must be within the precision.
+    pub(crate) fn check(&self) -> Result<()> {
+        if let Some((min, max)) = self.data_type.decimal_precision_range() {
+            if self.precision < min || self.precision > max {
+                return Err(Error::InvalidArgument(format!(
+                    "precision {} is invalid for column {:?}",
+                    self.precision, self
+                )));
+            }
+            if self.scale > self.precision {
+                return Err(Error::InvalidArgument(format!(
+                    "scale {} is invalid for column {:?}",
+                    self.scale, self
+                )));
+            }
+        }
+        match self.default {
+            Some((physical_type, _)) if physical_type != self.data_type.physical_type() => {
+                Err(Error::InvalidArgument(format!(
+                    "default value of type {:?} is invalid for column {:?}",
+                    physical_type, self
+                )))
+            }
+            Some((physical_type, ref value)) if self.precision().is_some() => {
+                let unscaled = util::decode_decimal(physical_type, value);
+                let max = util::decimal_max(self.precision);
+                if unscaled < -max || unscaled > max {
+                    Err(Error::InvalidArgument(format!(
+                        "default value {} exceeds the precision of column {:?}",
+                        util::decimal_to_string(unscaled, self.scale),
+                        self
+                    )))
+                } else {
+                    Ok(())
+                }
+            }
+            _ => Ok(()),
+        }
+    }
+
     pub(crate) fn into_pb(self, is_key: bool) -> ColumnSchemaPb {
+        let type_attributes = self.data_type.decimal_precision_range().map(|_| {
+            ColumnTypeAttributesPb {
+                precision: Some(i32::from(self.precision)),
+                scale: Some(i32::from(self.scale)),
+            }
+        });
         ColumnSchemaPb {
             name: self.name,
             type_: self.data_type.to_pb(),
@@ -110,25 +246,45 @@ impl Column {
             compression: Some(self.compression.to_pb()),
             // TODO: checked cast.
             cfile_block_size: Some(self.cfile_block_size as i32),
+            type_attributes,
+            read_default_value: self.default.as_ref().map(|&(_, ref value)| value.clone()),
+            write_default_value: self.default.map(|(_, value)| value),
             ..Default::default()
         }
     }
 
     pub(crate) fn from_pb(pb: ColumnSchemaPb) -> Result<Column> {
-        Ok(Column {
+        // Attributes which do not fit in a u8 are saturated, and rejected by the check below.
+        let attribute = |value: i32| cmp::min(value as u32, u32::from(u8::max_value())) as u8;
+        let (precision, scale) = pb.type_attributes.as_ref().map_or((0, 0), |attributes| {
+            (attribute(attributes.precision()), attribute(attributes.scale()))
+        });
+        let data_type = DataType::from_pb(pb.type_())?;
+        let column = Column {
             is_nullable: pb.is_nullable(),
-            data_type: DataType::from_pb(pb.type_())?,
+            data_type,
             compression: CompressionType::from_pb(pb.compression())?,
             encoding: EncodingType::from_pb(pb.encoding())?,
             cfile_block_size: pb.cfile_block_size() as u32,
+            precision,
+            scale,
+            default: pb
+                .write_default_value
+                .or(pb.read_default_value)
+                .map(|value| (data_type.physical_type(), value)),
             name: pb.name,
-        })
+        };
+        column.check()?;
+        Ok(column)
     }
 }
 
 impl fmt::Debug for Column {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{:?} {:?}", self.name, self.data_type)?;
+        if let (Some(precision), Some(scale)) = (self.precision(), self.scale()) {
+            write!(f, "({}, {})", precision, scale)?;
+        }
         if !self.is_nullable {
             write!(f, " NOT NULL")?;
         }
@@ -179,6 +335,19 @@ impl<'a> ColumnSelector for &'a str {
     }
 }
 
+/// Returns the physical type and encoded value of a column default value, or `None` if the value
+/// is null.
+pub(crate) fn encode_default_value<'data, V>(value: V) -> Option<(PhysicalType, Vec<u8>)>
+where
+    V: Value<'data>,
+{
+    if value.is_null() {
+        None
+    } else {
+        Some((V::PHYSICAL_TYPE, value.encode()))
+    }
+}
+
 #[derive(Clone)]
 pub struct Schema {
     inner: Arc<Inner>,
@@ -410,6 +579,10 @@ impl SchemaBuilder {
 
         columns.extend(self.columns.drain(..));
 
+        for column in &columns {
+            column.check()?;
+        }
+
         Ok(Schema::new(columns, self.primary_key.len()))
     }
 }
@@ -417,6 +590,8 @@ impl SchemaBuilder {
 #[cfg(test)]
 pub mod tests {
 
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
     use super::*;
     use DataType;
 
@@ -461,4 +636,75 @@ pub mod tests {
     fn test_create_schema() {
         all_types_schema();
     }
+
+    #[test]
+    fn test_decimal_columns() {
+        let column = Column::new("d", DataType::Decimal64);
+        assert_eq!(Some(18), column.precision());
+        assert_eq!(Some(0), column.scale());
+        assert_eq!(None, Column::new("i", DataType::Int64).precision());
+
+        let schema = |column: Column| {
+            SchemaBuilder::new()
+                .add_column(Column::new("key", DataType::Int32).set_not_null())
+                .add_column(column)
+                .set_primary_key(vec!["key"])
+                .build()
+        };
+        assert!(schema(column.clone().set_precision(10).set_scale(10)).is_ok());
+        assert!(schema(column.clone().set_precision(9)).is_err());
+        assert!(schema(column.clone().set_precision(19)).is_err());
+        assert!(schema(column.clone().set_precision(12).set_scale(13)).is_err());
+        assert!(schema(Column::new("d", DataType::Decimal32).set_precision(1)).is_ok());
+        assert!(schema(Column::new("d", DataType::Decimal128).set_precision(38)).is_ok());
+
+        let column = Column::new("d", DataType::Decimal32).set_precision(4);
+        assert!(column.clone().set_default_value(9999i32).check().is_ok());
+        assert!(column.clone().set_default_value(-9999i32).check().is_ok());
+        assert!(column.clone().set_default_value(10000i32).check().is_err());
+
+        let mut pb = column.into_pb(false);
+        assert!(Column::from_pb(pb.clone()).is_ok());
+        pb.type_attributes.as_mut().unwrap().precision = Some(260);
+        assert!(Column::from_pb(pb.clone()).is_err());
+        pb.type_attributes.as_mut().unwrap().precision = Some(4);
+        pb.type_attributes.as_mut().unwrap().scale = Some(-1);
+        assert!(Column::from_pb(pb).is_err());
+    }
+
+    #[test]
+    fn test_default_values() {
+        let column = Column::new("i", DataType::Int32).set_default_value(42i32);
+        assert_eq!(Some(42), column.default_value::<i32>().unwrap());
+        assert!(column.default_value::<i64>().is_err());
+        assert!(column.check().is_ok());
+
+        let column = Column::new("s", DataType::String).set_default_value("foo");
+        assert_eq!(Some("foo"), column.default_value::<&str>().unwrap());
+        assert_eq!(
+            Some("foo".to_string()),
+            Column::from_pb(column.into_pb(false))
+                .unwrap()
+                .default_value::<String>()
+                .unwrap()
+        );
+
+        let timestamp = UNIX_EPOCH + Duration::from_micros(1_500_000);
+        let column = Column::new("t", DataType::Timestamp).set_default_value(timestamp);
+        assert!(column.check().is_ok());
+        assert_eq!(Some(timestamp), column.default_value::<SystemTime>().unwrap());
+        assert_eq!(
+            Some(1_500_000),
+            Column::from_pb(column.into_pb(false))
+                .unwrap()
+                .default_value::<i64>()
+                .unwrap()
+        );
+
+        let column = Column::new("s", DataType::String).set_default_value(None::<&str>);
+        assert_eq!(None, column.default_value::<&str>().unwrap());
+
+        let column = Column::new("i", DataType::Int32).set_default_value(42i64);
+        assert!(column.check().is_err());
+    }
 }
@@ -0,0 +1,564 @@
+//! Serde support for rows and schemas.
+//!
+//! Rows serialize as a map from column name to value, including only the columns which are set.
+//! Null values serialize as `None`, timestamps serialize as microseconds since the Unix epoch,
+//! which is the precision of Kudu timestamps, and decimals serialize as strings in order to
+//! preserve their precision.
+//!
+//! Schemas serialize as their columns and the names of the primary key columns, and are validated
+//! with a `SchemaBuilder` when deserialized. Column default values serialize in the same format as
+//! row values, and are decoded with the data type of the column when deserialized.
+
+use std::fmt;
+
+use serde::de::{self, Deserialize, Deserializer, SeqAccess, Visitor};
+use serde::ser::{self, Serialize, SerializeMap, SerializeStruct, Serializer};
+
+use util;
+use Column;
+use CompressionType;
+use DataType;
+use EncodingType;
+use Row;
+use Schema;
+use SchemaBuilder;
+use Value;
+
+/// A cell of a row, which serializes as the cell value.
+struct Cell<'a, 'data: 'a> {
+    row: &'a Row<'data>,
+    idx: usize,
+}
+
+impl<'a, 'data> Serialize for Cell<'a, 'data> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let row = self.row;
+        let idx = self.idx;
+        if row.is_null(idx).unwrap() {
+            return serializer.serialize_none();
+        }
+
+        match row.schema().columns()[idx].data_type() {
+            DataType::Bool => serializer.serialize_bool(row.get(idx).unwrap()),
+            DataType::Int8 => serializer.serialize_i8(row.get(idx).unwrap()),
+            DataType::Int16 => serializer.serialize_i16(row.get(idx).unwrap()),
+            DataType::Int32 => serializer.serialize_i32(row.get(idx).unwrap()),
+            DataType::Int64 | DataType::Timestamp => {
+                serializer.serialize_i64(row.get(idx).unwrap())
+            }
+            DataType::Float => serializer.serialize_f32(row.get(idx).unwrap()),
+            DataType::Double => serializer.serialize_f64(row.get(idx).unwrap()),
+            DataType::Binary => serializer.serialize_bytes(row.get(idx).unwrap()),
+            DataType::String => serializer.serialize_str(row.get(idx).unwrap()),
+            DataType::Decimal32 | DataType::Decimal64 | DataType::Decimal128 => {
+                let scale = row.schema().columns()[idx].scale().unwrap();
+                serializer.serialize_str(&util::decimal_to_string(
+                    util::decimal_cell(row, idx).unwrap(),
+                    scale,
+                ))
+            }
+        }
+    }
+}
+
+impl<'data> Serialize for Row<'data> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let columns = self.schema().columns();
+        let num_set = (0..columns.len())
+            .filter(|&idx| self.is_set(idx).unwrap())
+            .count();
+        let mut map = serializer.serialize_map(Some(num_set))?;
+        for (idx, column) in columns.iter().enumerate() {
+            if self.is_set(idx).unwrap() {
+                map.serialize_entry(column.name(), &Cell { row: self, idx })?;
+            }
+        }
+        map.end()
+    }
+}
+
+/// The default value of a column, which serializes as the value.
+struct ColumnDefault<'a>(&'a Column);
+
+/// Returns the default value of a column which has a default value.
+fn default_value<'a, V, E>(column: &'a Column) -> Result<V, E>
+where
+    V: Value<'a>,
+    E: ser::Error,
+{
+    column
+        .default_value()
+        .map_err(E::custom)?
+        .ok_or_else(|| E::custom("column has no default value"))
+}
+
+impl<'a> Serialize for ColumnDefault<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let column = self.0;
+        match column.data_type() {
+            DataType::Bool => serializer.serialize_bool(default_value(column)?),
+            DataType::Int8 => serializer.serialize_i8(default_value(column)?),
+            DataType::Int16 => serializer.serialize_i16(default_value(column)?),
+            DataType::Int32 => serializer.serialize_i32(default_value(column)?),
+            DataType::Int64 | DataType::Timestamp => {
+                serializer.serialize_i64(default_value(column)?)
+            }
+            DataType::Float => serializer.serialize_f32(default_value(column)?),
+            DataType::Double => serializer.serialize_f64(default_value(column)?),
+            DataType::Binary => serializer.serialize_bytes(default_value(column)?),
+            DataType::String => serializer.serialize_str(default_value(column)?),
+            DataType::Decimal32 | DataType::Decimal64 | DataType::Decimal128 => {
+                let unscaled = match column.data_type() {
+                    DataType::Decimal32 => i128::from(default_value::<i32, _>(column)?),
+                    DataType::Decimal64 => i128::from(default_value::<i64, _>(column)?),
+                    _ => default_value::<i128, _>(column)?,
+                };
+                serializer.serialize_str(&util::decimal_to_string(
+                    unscaled,
+                    column.scale().unwrap(),
+                ))
+            }
+        }
+    }
+}
+
+impl Serialize for Column {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let default = if self.has_default_value() {
+            Some(ColumnDefault(self))
+        } else {
+            None
+        };
+        let mut column = serializer.serialize_struct("Column", 9)?;
+        column.serialize_field("name", self.name())?;
+        column.serialize_field("data_type", &self.data_type())?;
+        column.serialize_field("is_nullable", &self.is_nullable())?;
+        column.serialize_field("compression", &self.compression())?;
+        column.serialize_field("encoding", &self.encoding())?;
+        column.serialize_field("cfile_block_size", &self.cfile_block_size().unwrap_or(0))?;
+        column.serialize_field("precision", &self.precision())?;
+        column.serialize_field("scale", &self.scale())?;
+        column.serialize_field("default", &default)?;
+        column.end()
+    }
+}
+
+/// A deserialized default value, which is decoded with the data type of the column.
+#[derive(Debug)]
+enum DefaultValue {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+    Bytes(Vec<u8>),
+}
+
+impl<'de> Deserialize<'de> for DefaultValue {
+    fn deserialize<D>(deserializer: D) -> Result<DefaultValue, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(DefaultValueVisitor)
+    }
+}
+
+struct DefaultValueVisitor;
+
+impl<'de> Visitor<'de> for DefaultValueVisitor {
+    type Value = DefaultValue;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a column default value")
+    }
+
+    fn visit_bool<E>(self, value: bool) -> Result<DefaultValue, E>
+    where
+        E: de::Error,
+    {
+        Ok(DefaultValue::Bool(value))
+    }
+
+    fn visit_i64<E>(self, value: i64) -> Result<DefaultValue, E>
+    where
+        E: de::Error,
+    {
+        Ok(DefaultValue::Int(value))
+    }
+
+    fn visit_u64<E>(self, value: u64) -> Result<DefaultValue, E>
+    where
+        E: de::Error,
+    {
+        if value > i64::max_value() as u64 {
+            return Err(E::custom(format!("default value {} is out of range", value)));
+        }
+        Ok(DefaultValue::Int(value as i64))
+    }
+
+    fn visit_f64<E>(self, value: f64) -> Result<DefaultValue, E>
+    where
+        E: de::Error,
+    {
+        Ok(DefaultValue::Float(value))
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<DefaultValue, E>
+    where
+        E: de::Error,
+    {
+        Ok(DefaultValue::String(value.to_owned()))
+    }
+
+    fn visit_string<E>(self, value: String) -> Result<DefaultValue, E>
+    where
+        E: de::Error,
+    {
+        Ok(DefaultValue::String(value))
+    }
+
+    fn visit_bytes<E>(self, value: &[u8]) -> Result<DefaultValue, E>
+    where
+        E: de::Error,
+    {
+        Ok(DefaultValue::Bytes(value.to_owned()))
+    }
+
+    fn visit_byte_buf<E>(self, value: Vec<u8>) -> Result<DefaultValue, E>
+    where
+        E: de::Error,
+    {
+        Ok(DefaultValue::Bytes(value))
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<DefaultValue, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(byte) = seq.next_element()? {
+            bytes.push(byte);
+        }
+        Ok(DefaultValue::Bytes(bytes))
+    }
+}
+
+/// Converts an integer default value to a narrower integer type, or returns `None` from the
+/// enclosing function if the value is out of range.
+macro_rules! narrow {
+    ($value:expr, $ty:ty) => {{
+        if $value < i64::from(<$ty>::min_value()) || $value > i64::from(<$ty>::max_value()) {
+            return None;
+        }
+        $value as $ty
+    }};
+}
+
+impl DefaultValue {
+    /// Sets the value as the default value of the column, decoded with the column's data type.
+    fn set_default(&self, column: Column) -> Option<Column> {
+        let column = match (column.data_type(), self) {
+            (DataType::Bool, &DefaultValue::Bool(value)) => column.set_default_value(value),
+            (DataType::Int8, &DefaultValue::Int(value)) => {
+                column.set_default_value(narrow!(value, i8))
+            }
+            (DataType::Int16, &DefaultValue::Int(value)) => {
+                column.set_default_value(narrow!(value, i16))
+            }
+            (DataType::Int32, &DefaultValue::Int(value)) => {
+                column.set_default_value(narrow!(value, i32))
+            }
+            (DataType::Int64, &DefaultValue::Int(value))
+            | (DataType::Timestamp, &DefaultValue::Int(value)) => column.set_default_value(value),
+            (DataType::Float, &DefaultValue::Int(value)) => column.set_default_value(value as f32),
+            (DataType::Float, &DefaultValue::Float(value)) => {
+                column.set_default_value(value as f32)
+            }
+            (DataType::Double, &DefaultValue::Int(value)) => {
+                column.set_default_value(value as f64)
+            }
+            (DataType::Double, &DefaultValue::Float(value)) => column.set_default_value(value),
+            (DataType::Binary, &DefaultValue::Bytes(ref value)) => {
+                column.set_default_value(&value[..])
+            }
+            (DataType::String, &DefaultValue::String(ref value)) => {
+                column.set_default_value(&value[..])
+            }
+            (data_type, &DefaultValue::String(ref value))
+                if data_type.decimal_precision_range().is_some() =>
+            {
+                let unscaled = util::decimal_from_string(value, column.scale().unwrap())?;
+                let max = util::decimal_max(column.precision().unwrap());
+                if unscaled < -max || unscaled > max {
+                    return None;
+                }
+                match data_type {
+                    DataType::Decimal32 => column.set_default_value(unscaled as i32),
+                    DataType::Decimal64 => column.set_default_value(unscaled as i64),
+                    _ => column.set_default_value(unscaled),
+                }
+            }
+            _ => return None,
+        };
+        Some(column)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "Column")]
+struct ColumnDef {
+    name: String,
+    data_type: DataType,
+    is_nullable: bool,
+    compression: CompressionType,
+    encoding: EncodingType,
+    cfile_block_size: u32,
+    #[serde(default)]
+    precision: Option<u8>,
+    #[serde(default)]
+    scale: Option<u8>,
+    #[serde(default)]
+    default: Option<DefaultValue>,
+}
+
+impl<'de> Deserialize<'de> for Column {
+    fn deserialize<D>(deserializer: D) -> Result<Column, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let ColumnDef {
+            name,
+            data_type,
+            is_nullable,
+            compression,
+            encoding,
+            cfile_block_size,
+            precision,
+            scale,
+            default,
+        } = ColumnDef::deserialize(deserializer)?;
+        let mut column = Column::new(name, data_type)
+            .set_is_nullable(is_nullable)
+            .set_compression(compression)
+            .set_encoding(encoding)
+            .set_cfile_block_size(cfile_block_size);
+        if let Some(precision) = precision {
+            column = column.set_precision(precision);
+        }
+        if let Some(scale) = scale {
+            column = column.set_scale(scale);
+        }
+        match default {
+            Some(default) => default.set_default(column.clone()).ok_or_else(|| {
+                de::Error::custom(format!(
+                    "default value {:?} is invalid for column {:?}",
+                    default, column
+                ))
+            }),
+            None => Ok(column),
+        }
+    }
+}
+
+impl Serialize for Schema {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let primary_key = self
+            .primary_key()
+            .iter()
+            .map(Column::name)
+            .collect::<Vec<_>>();
+        let mut schema = serializer.serialize_struct("Schema", 2)?;
+        schema.serialize_field("columns", self.columns())?;
+        schema.serialize_field("primary_key", &primary_key)?;
+        schema.end()
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "Schema")]
+struct SchemaDef {
+    columns: Vec<Column>,
+    primary_key: Vec<String>,
+}
+
+impl<'de> Deserialize<'de> for Schema {
+    fn deserialize<D>(deserializer: D) -> Result<Schema, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let SchemaDef {
+            columns,
+            primary_key,
+        } = SchemaDef::deserialize(deserializer)?;
+        columns
+            .into_iter()
+            .fold(SchemaBuilder::new(), SchemaBuilder::add_column)
+            .set_primary_key(primary_key)
+            .build()
+            .map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use serde_json;
+
+    use super::*;
+    use schema::tests::all_types_schema;
+
+    #[test]
+    fn schema() {
+        let schema = all_types_schema();
+        let json = serde_json::to_string(&schema).unwrap();
+        assert_eq!(schema, serde_json::from_str::<Schema>(&json).unwrap());
+
+        let column = &schema.columns()[1];
+        let json = serde_json::to_string(column).unwrap();
+        assert_eq!(column, &serde_json::from_str::<Column>(&json).unwrap());
+
+        let json = r#"{
+            "columns": [{
+                "name": "key",
+                "data_type": "Int32",
+                "is_nullable": false,
+                "compression": "Default",
+                "encoding": "Auto",
+                "cfile_block_size": 0
+            }],
+            "primary_key": ["missing"]
+        }"#;
+        assert!(serde_json::from_str::<Schema>(json).is_err());
+    }
+
+    #[test]
+    fn default_values() {
+        let timestamp = UNIX_EPOCH + Duration::from_millis(1500);
+        let schema = SchemaBuilder::new()
+            .add_column(Column::new("key", DataType::Int32).set_not_null())
+            .add_column(Column::new("bool", DataType::Bool).set_default_value(true))
+            .add_column(Column::new("i8", DataType::Int8).set_default_value(-8i8))
+            .add_column(Column::new("i64", DataType::Int64).set_default_value(64i64))
+            .add_column(Column::new("ts", DataType::Timestamp).set_default_value(timestamp))
+            .add_column(Column::new("f32", DataType::Float).set_default_value(1.5f32))
+            .add_column(Column::new("f64", DataType::Double).set_default_value(2.5f64))
+            .add_column(Column::new("binary", DataType::Binary).set_default_value(&b"bar"[..]))
+            .add_column(Column::new("string", DataType::String).set_default_value("foo"))
+            .add_column(
+                Column::new("decimal", DataType::Decimal64)
+                    .set_precision(10)
+                    .set_scale(2)
+                    .set_default_value(-12_345i64),
+            ).add_column(Column::new("unset", DataType::Double))
+            .set_primary_key(vec!["key"])
+            .build()
+            .unwrap();
+
+        let json = serde_json::to_value(&schema).unwrap();
+        let defaults = json["columns"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|column| column["default"].clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                json!(null),
+                json!(true),
+                json!(-8),
+                json!(64),
+                json!(1_500_000),
+                json!(1.5),
+                json!(2.5),
+                json!([98, 97, 114]),
+                json!("foo"),
+                json!("-123.45"),
+                json!(null),
+            ],
+            defaults
+        );
+        assert_eq!(schema, serde_json::from_value::<Schema>(json).unwrap());
+
+        // Default values must be valid for the column type.
+        let column = |data_type: &str, default: serde_json::Value| {
+            serde_json::from_value::<Column>(json!({
+                "name": "c",
+                "data_type": data_type,
+                "is_nullable": true,
+                "compression": "Default",
+                "encoding": "Auto",
+                "cfile_block_size": 0,
+                "precision": 4,
+                "scale": 2,
+                "default": default,
+            }))
+        };
+        assert_eq!(
+            Some(-12i8),
+            column("Int8", json!(-12)).unwrap().default_value().unwrap()
+        );
+        assert_eq!(
+            Some(1234i32),
+            column("Decimal32", json!("12.34"))
+                .unwrap()
+                .default_value()
+                .unwrap()
+        );
+        assert!(column("Int8", json!(300)).is_err());
+        assert!(column("Int8", json!("foo")).is_err());
+        assert!(column("String", json!(42)).is_err());
+        assert!(column("Decimal32", json!("123.45")).is_err());
+        assert!(column("Decimal32", json!("1.234")).is_err());
+    }
+
+    #[test]
+    fn row() {
+        let schema = SchemaBuilder::new()
+            .add_column(Column::new("key", DataType::Int32).set_not_null())
+            .add_column(Column::new("ts", DataType::Timestamp).set_not_null())
+            .add_column(Column::new("name", DataType::String).set_nullable())
+            .add_column(Column::new("data", DataType::Binary).set_nullable())
+            .add_column(Column::new("unset", DataType::Double).set_nullable())
+            .set_primary_key(vec!["key"])
+            .build()
+            .unwrap();
+
+        let mut row = schema.new_row();
+        row.set("key", 42i32).unwrap();
+        row.set("ts", UNIX_EPOCH + Duration::from_millis(1500)).unwrap();
+        row.set("name", "foo").unwrap();
+        row.set_null("data").unwrap();
+
+        assert_eq!(
+            json!({
+                "key": 42,
+                "ts": 1_500_000,
+                "name": "foo",
+                "data": null
+            }),
+            serde_json::to_value(&row).unwrap()
+        );
+
+        row.set("data", &b"bar"[..]).unwrap();
+        assert_eq!(
+            json!([98, 97, 114]),
+            serde_json::to_value(&row).unwrap()["data"]
+        );
+    }
+}
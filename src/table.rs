@@ -1,21 +1,29 @@
 use pb::master::alter_table_request_pb::{
-    AddColumn, AddRangePartition, DropColumn, DropRangePartition, RenameColumn, Step, StepType,
+    AddColumn, AddRangePartition, AlterColumn, DropColumn, DropRangePartition, RenameColumn, Step,
+    StepType,
 };
 use pb::master::{AlterTableRequestPb, CreateTableRequestPb};
 use pb::partition_schema_pb::{ColumnIdentifierPb, HashBucketSchemaPb, RangeSchemaPb};
-use pb::PartitionSchemaPb;
+use pb::{ColumnSchemaDeltaPb, PartitionSchemaPb};
 
 use meta_cache::{Entry, Lookup, TableLocations};
 use partition::PartitionSchema;
+use schema::encode_default_value;
 use scanner::ScanBuilder;
 use tablet::TabletInfo;
+use Client;
 use Column;
+use CompressionType;
+use DataType;
+use EncodingType;
 use Error;
 use OperationEncoder;
+use PhysicalType;
 use Result;
 use Row;
 use Schema;
 use TableId;
+use Value;
 use Writer;
 use WriterConfig;
 
@@ -29,6 +37,7 @@ pub struct Table {
     partition_schema: PartitionSchema,
     num_replicas: u32,
     table_locations: TableLocations,
+    client: Client,
 }
 
 impl Table {
@@ -39,6 +48,7 @@ impl Table {
         partition_schema: PartitionSchema,
         num_replicas: u32,
         table_locations: TableLocations,
+        client: Client,
     ) -> Table {
         Table {
             name,
@@ -47,6 +57,7 @@ impl Table {
             partition_schema,
             num_replicas,
             table_locations,
+            client,
         }
     }
 
@@ -75,7 +86,7 @@ impl Table {
     }
 
     pub fn scan_builder(&self) -> ScanBuilder {
-        ScanBuilder::new(self.schema.clone(), self.table_locations.clone())
+        ScanBuilder::new(self.clone())
     }
 
     // TODO: should this be a stream?
@@ -91,6 +102,10 @@ impl Table {
     pub(crate) fn table_locations(&self) -> &TableLocations {
         &self.table_locations
     }
+
+    pub(crate) fn client(&self) -> &Client {
+        &self.client
+    }
 }
 
 pub struct Tablets {
@@ -143,7 +158,7 @@ pub enum RangePartitionBound {
 }
 
 impl RangePartitionBound {
-    fn row(&self) -> &Row<'static> {
+    pub(crate) fn row(&self) -> &Row<'static> {
         match *self {
             RangePartitionBound::Inclusive(ref row) | RangePartitionBound::Exclusive(ref row) => {
                 row
@@ -338,14 +353,26 @@ impl AlterTableBuilder {
         self
     }
 
+    /// Adds a column to the table. Columns which are not nullable must have a default value.
     pub fn add_column(&mut self, column: Column) -> &mut AlterTableBuilder {
-        self.pb.alter_schema_steps.push(Step {
-            type_: Some(StepType::AddColumn as i32),
-            add_column: Some(AddColumn {
-                schema: column.into_pb(false),
-            }),
-            ..Default::default()
-        });
+        if self.result.is_ok() {
+            self.result = column.check();
+        }
+        if self.result.is_ok() && !column.is_nullable() && !column.has_default_value() {
+            self.result = Err(Error::InvalidArgument(format!(
+                "column {:?} must be nullable or have a default value",
+                column
+            )));
+        }
+        if self.result.is_ok() {
+            self.pb.alter_schema_steps.push(Step {
+                type_: Some(StepType::AddColumn as i32),
+                add_column: Some(AddColumn {
+                    schema: column.into_pb(false),
+                }),
+                ..Default::default()
+            });
+        }
         self
     }
 
@@ -363,6 +390,23 @@ impl AlterTableBuilder {
         self
     }
 
+    /// Alters the storage attributes or default value of a column.
+    pub fn alter_column(&mut self, alter_column: AlterColumnBuilder) -> &mut AlterTableBuilder {
+        if self.result.is_ok() {
+            self.result = alter_column.check();
+        }
+        if self.result.is_ok() {
+            self.pb.alter_schema_steps.push(Step {
+                type_: Some(StepType::AlterColumn as i32),
+                alter_column: Some(AlterColumn {
+                    delta: Some(alter_column.into_pb()),
+                }),
+                ..Default::default()
+            });
+        }
+        self
+    }
+
     pub fn rename_column<S1, S2>(&mut self, old_name: S1, new_name: S2) -> &mut AlterTableBuilder
     where
         S1: Into<String>,
@@ -447,6 +491,104 @@ impl Default for AlterTableBuilder {
     }
 }
 
+/// Alters the storage attributes and default value of an existing column. Attributes which are
+/// not set are left unchanged.
+///
+/// Alterations are applied to the table with `AlterTableBuilder::alter_column`.
+#[derive(Clone, Debug)]
+pub struct AlterColumnBuilder {
+    name: String,
+    data_type: DataType,
+    encoding: Option<EncodingType>,
+    compression: Option<CompressionType>,
+    cfile_block_size: Option<u32>,
+    /// The new default value, or `Some(None)` if the default value is removed.
+    default: Option<Option<(PhysicalType, Vec<u8>)>>,
+}
+
+impl AlterColumnBuilder {
+    /// Returns a builder for alterations to the column, which should be taken from the current
+    /// schema of the table.
+    pub fn new(column: &Column) -> AlterColumnBuilder {
+        AlterColumnBuilder {
+            name: column.name().to_owned(),
+            data_type: column.data_type(),
+            encoding: None,
+            compression: None,
+            cfile_block_size: None,
+            default: None,
+        }
+    }
+
+    pub fn set_encoding(mut self, encoding: EncodingType) -> AlterColumnBuilder {
+        self.encoding = Some(encoding);
+        self
+    }
+
+    pub fn set_compression(mut self, compression: CompressionType) -> AlterColumnBuilder {
+        self.compression = Some(compression);
+        self
+    }
+
+    pub fn set_cfile_block_size(mut self, cfile_block_size: u32) -> AlterColumnBuilder {
+        self.cfile_block_size = Some(cfile_block_size);
+        self
+    }
+
+    /// Sets the default value of the column. A null value removes the default.
+    pub fn set_default_value<'data, V>(mut self, value: V) -> AlterColumnBuilder
+    where
+        V: Value<'data>,
+    {
+        self.default = Some(encode_default_value(value));
+        self
+    }
+
+    pub fn remove_default_value(mut self) -> AlterColumnBuilder {
+        self.default = Some(None);
+        self
+    }
+
+    /// Checks that the encoding and the type of the default value are valid for the column.
+    fn check(&self) -> Result<()> {
+        if let Some(encoding) = self.encoding {
+            if !self.data_type.encodings().contains(&encoding) {
+                return Err(Error::InvalidArgument(format!(
+                    "encoding {:?} is invalid for column {:?} of type {:?}",
+                    encoding, self.name, self.data_type
+                )));
+            }
+        }
+        match self.default {
+            Some(Some((physical_type, _))) if physical_type != self.data_type.physical_type() => {
+                Err(Error::InvalidArgument(format!(
+                    "default value of type {:?} is invalid for column {:?} of type {:?}",
+                    physical_type, self.name, self.data_type
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn into_pb(self) -> ColumnSchemaDeltaPb {
+        let (default_value, remove_default) = match self.default {
+            Some(Some((_, value))) => (Some(value), None),
+            Some(None) => (None, Some(true)),
+            None => (None, None),
+        };
+        ColumnSchemaDeltaPb {
+            name: Some(self.name),
+            encoding: self.encoding.map(EncodingType::to_pb),
+            compression: self.compression.map(CompressionType::to_pb),
+            // TODO: checked cast.
+            block_size: self.cfile_block_size.map(|size| size as i32),
+            default_value,
+            remove_default,
+            ..Default::default()
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -1,11 +1,18 @@
+use std::cmp;
 use std::fmt;
-use std::sync::atomic::{AtomicBool, Ordering::Relaxed};
-use std::sync::Arc;
+use std::net::{IpAddr, ToSocketAddrs};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering::Relaxed};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Duration;
 
 use krpc;
+use parking_lot::Mutex;
 
 use partition::PartitionKey;
 use replica::{Replica, ReplicaSet};
+use util;
 use HostPort;
 use Partition;
 use PartitionSchema;
@@ -86,6 +93,7 @@ pub(crate) struct TabletReplica {
     pub id: TabletServerId,
     pub rpc_addrs: Box<[HostPort]>,
     pub proxy: krpc::Proxy,
+    pub locality: Arc<Locality>,
     pub is_leader: AtomicBool,
     pub is_stale: AtomicBool,
 }
@@ -131,6 +139,99 @@ impl Replica for TabletReplica {
     fn mark_stale(&self) {
         self.is_stale.store(true, Relaxed)
     }
+
+    fn is_local(&self) -> bool {
+        self.locality.is_local.load(Relaxed)
+    }
+
+    fn latency(&self) -> Option<Duration> {
+        match self.locality.latency_micros.load(Relaxed) {
+            0 => None,
+            micros => Some(Duration::from_micros(micros)),
+        }
+    }
+
+    fn observe_latency(&self, latency: Duration) {
+        let micros = cmp::max(
+            latency.as_secs() * 1_000_000 + u64::from(latency.subsec_micros()),
+            1,
+        );
+        // Exponentially weighted moving average, weighting each observation by 1/4. Concurrent
+        // observations may be lost, which is harmless.
+        let average = match self.locality.latency_micros.load(Relaxed) {
+            0 => micros,
+            average => (average * 3 + micros) / 4,
+        };
+        self.locality.latency_micros.store(average, Relaxed);
+    }
+}
+
+/// The locality of a tablet server, shared by the replicas hosted on the tablet server.
+pub(crate) struct Locality {
+    /// Whether the tablet server is hosted on the local machine.
+    is_local: AtomicBool,
+    /// The average observed RPC latency in microseconds, or 0 if none has been observed.
+    latency_micros: AtomicU64,
+}
+
+impl Locality {
+    /// Returns the locality of the tablet server with the provided RPC addresses.
+    ///
+    /// Hostnames are resolved by a shared background thread, so that DNS lookups do not block the
+    /// caller. Until they are resolved the tablet server is considered remote.
+    pub fn new(rpc_addrs: &[HostPort]) -> Arc<Locality> {
+        let locality = Arc::new(Locality {
+            is_local: AtomicBool::new(false),
+            latency_micros: AtomicU64::new(0),
+        });
+
+        let mut hostnames = Vec::new();
+        for hostport in rpc_addrs {
+            match IpAddr::from_str(&hostport.host()) {
+                Ok(addr) => {
+                    if util::is_local_addr(&addr) {
+                        locality.is_local.store(true, Relaxed);
+                        return locality;
+                    }
+                }
+                Err(_) => hostnames.push(hostport.clone()),
+            }
+        }
+
+        if !hostnames.is_empty() {
+            // The resolver thread only exits if it panics, in which case the tablet server is
+            // considered remote.
+            let _ = RESOLVER.lock().send((hostnames, locality.clone()));
+        }
+        locality
+    }
+}
+
+lazy_static! {
+    /// Resolves the hostnames of tablet servers, and records whether they are local.
+    static ref RESOLVER: Mutex<mpsc::Sender<(Vec<HostPort>, Arc<Locality>)>> = {
+        let (sender, receiver) = mpsc::channel::<(Vec<HostPort>, Arc<Locality>)>();
+        thread::Builder::new()
+            .name("kudu-resolver".to_string())
+            .spawn(move || {
+                for (hostnames, locality) in receiver {
+                    let is_local = hostnames
+                        .iter()
+                        .any(|hostport| match hostport.to_socket_addrs() {
+                            Ok(mut addrs) => addrs.any(|addr| util::is_local_addr(&addr.ip())),
+                            Err(error) => {
+                                warn!(
+                                    "failed to resolve tablet server address {}: {}",
+                                    hostport, error
+                                );
+                                false
+                            }
+                        });
+                    locality.is_local.store(is_local, Relaxed);
+                }
+            }).expect("failed to spawn resolver thread");
+        Mutex::new(sender)
+    };
 }
 
 impl fmt::Debug for TabletReplica {
@@ -0,0 +1,184 @@
+use std::marker::PhantomData;
+
+use value::Value;
+use Error;
+use Result;
+use Row;
+use Schema;
+
+/// A Rust type which maps to the rows of a table, with a field for each of a subset of the
+/// columns.
+///
+/// `TypedRow` is usually implemented with `#[derive(TypedRow)]` from the `kudu-derive` crate.
+/// Types are converted to and from rows with a `RowMapper`, which checks the fields against the
+/// schema once up front.
+pub trait TypedRow: Sized {
+    /// Checks the fields against the schema, and returns the index of the column of each field.
+    fn column_indices(schema: &Schema) -> Result<Vec<usize>>;
+
+    /// Writes the fields to a row.
+    ///
+    /// # Unsafety
+    ///
+    /// The column indices must have been returned by `column_indices` for the row's schema.
+    unsafe fn write_row(self, column_indices: &[usize], row: &mut Row<'static>);
+
+    /// Reads the fields from a row.
+    ///
+    /// Returns an error if a column is unset, or is null and the field type is not nullable.
+    fn read_row(column_indices: &[usize], row: &Row) -> Result<Self>;
+}
+
+/// Returns the index of the named column, after checking that values of type `V` can be read
+/// from and written to the column.
+pub fn check_column<V>(schema: &Schema, name: &str) -> Result<usize>
+where
+    V: Value<'static>,
+{
+    let idx = schema
+        .column_index(name)
+        .ok_or_else(|| Error::InvalidArgument(format!("column {:?} not found", name)))?;
+    let column = &schema.columns()[idx];
+    if !V::can_read_from(column.data_type()) || !V::can_write_to(column.data_type()) {
+        return Err(Error::InvalidArgument(format!(
+            "type {:?} is invalid for column {:?}",
+            V::DATA_TYPE,
+            column
+        )));
+    }
+    if V::is_nullable() && !column.is_nullable() {
+        return Err(Error::InvalidArgument(format!(
+            "nullable type is invalid for column {:?}",
+            column
+        )));
+    }
+    Ok(idx)
+}
+
+/// Converts values of a `TypedRow` type to and from the rows of a schema.
+pub struct RowMapper<T> {
+    schema: Schema,
+    column_indices: Vec<usize>,
+    _marker: PhantomData<fn(T) -> T>,
+}
+
+impl<T> RowMapper<T>
+where
+    T: TypedRow,
+{
+    /// Creates a row mapper for the schema.
+    ///
+    /// Rows written with the mapper use the schema, so it should be the table schema. Scanned rows
+    /// use the projected schema of the scan.
+    ///
+    /// Returns an error if a field's column does not exist, or does not match the field type.
+    pub fn new(schema: &Schema) -> Result<RowMapper<T>> {
+        let column_indices = T::column_indices(schema)?;
+        Ok(RowMapper {
+            schema: schema.clone(),
+            column_indices,
+            _marker: PhantomData,
+        })
+    }
+
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    /// Converts the value to a row, such as an insert for a `Writer`.
+    pub fn to_row(&self, value: T) -> Row<'static> {
+        let mut row = self.schema.new_row();
+        unsafe {
+            value.write_row(&self.column_indices, &mut row);
+        }
+        row
+    }
+
+    /// Converts a row, such as a scanned row, to a value.
+    ///
+    /// Returns an error if the row's schema does not match the mapper's schema.
+    pub fn from_row(&self, row: &Row) -> Result<T> {
+        if !row.schema().ref_eq(&self.schema) && row.schema() != &self.schema {
+            return Err(Error::InvalidArgument(
+                "row schema does not match the row mapper schema".to_string(),
+            ));
+        }
+        T::read_row(&self.column_indices, row)
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use Column;
+    use DataType;
+    use SchemaBuilder;
+
+    fn schema() -> Schema {
+        SchemaBuilder::new()
+            .add_column(Column::new("key", DataType::Int32).set_not_null())
+            .add_column(Column::new("val", DataType::String).set_nullable())
+            .set_primary_key(vec!["key"])
+            .build()
+            .unwrap()
+    }
+
+    struct KeyVal {
+        key: i32,
+        val: Option<String>,
+    }
+
+    impl TypedRow for KeyVal {
+        fn column_indices(schema: &Schema) -> Result<Vec<usize>> {
+            Ok(vec![
+                check_column::<i32>(schema, "key")?,
+                check_column::<Option<String>>(schema, "val")?,
+            ])
+        }
+
+        unsafe fn write_row(self, column_indices: &[usize], row: &mut Row<'static>) {
+            row.set_unchecked(column_indices[0], self.key);
+            row.set_unchecked(column_indices[1], self.val);
+        }
+
+        fn read_row(column_indices: &[usize], row: &Row) -> Result<KeyVal> {
+            Ok(KeyVal {
+                key: row.get::<_, i32>(column_indices[0])?,
+                val: row.get::<_, Option<String>>(column_indices[1])?,
+            })
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let schema = schema();
+        let mapper = RowMapper::<KeyVal>::new(&schema).unwrap();
+
+        let row = mapper.to_row(KeyVal {
+            key: 42,
+            val: Some("foo".to_string()),
+        });
+        assert_eq!(42, row.get::<_, i32>("key").unwrap());
+        let key_val = mapper.from_row(&row).unwrap();
+        assert_eq!(42, key_val.key);
+        assert_eq!(Some("foo".to_string()), key_val.val);
+
+        let row = mapper.to_row(KeyVal { key: 7, val: None });
+        assert!(row.is_null("val").unwrap());
+        assert_eq!(None, mapper.from_row(&row).unwrap().val);
+
+        let other_schema = schema.primary_key_projection();
+        assert!(mapper.from_row(&other_schema.new_row()).is_err());
+        assert!(RowMapper::<KeyVal>::new(&other_schema).is_err());
+    }
+
+    #[test]
+    fn check_columns() {
+        let schema = schema();
+        assert_eq!(0, check_column::<i32>(&schema, "key").unwrap());
+        assert!(check_column::<i64>(&schema, "key").is_err());
+        assert!(check_column::<Option<i32>>(&schema, "key").is_err());
+        assert!(check_column::<i32>(&schema, "missing").is_err());
+    }
+}
@@ -1,5 +1,6 @@
 use std::collections::HashSet;
 use std::fmt;
+use std::iter;
 use std::net::IpAddr;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
@@ -11,6 +12,7 @@ use pb::HostPortPb;
 use timestamp::DateTime;
 use DataType;
 use Error;
+use PhysicalType;
 use Row;
 
 pub fn time_to_us(time: SystemTime) -> i64 {
@@ -54,6 +56,85 @@ fn fmt_timestamp(timestamp: SystemTime) -> impl fmt::Display {
     DateTime::from(timestamp)
 }
 
+/// Returns the unscaled value of a decimal cell, widened to an `i128`.
+pub fn decimal_cell(row: &Row, idx: usize) -> Result<i128, Error> {
+    match row.schema().columns()[idx].data_type() {
+        DataType::Decimal32 => row.get::<_, i32>(idx).map(i128::from),
+        DataType::Decimal64 => row.get::<_, i64>(idx).map(i128::from),
+        _ => row.get::<_, i128>(idx),
+    }
+}
+
+/// Returns the largest unscaled value of a decimal with the precision. The smallest unscaled value
+/// is its negation.
+pub fn decimal_max(precision: u8) -> i128 {
+    10i128.pow(u32::from(precision)) - 1
+}
+
+/// Decodes an encoded unscaled decimal value of the physical type, widened to an `i128`.
+pub fn decode_decimal(physical_type: PhysicalType, data: &[u8]) -> i128 {
+    unsafe {
+        match physical_type {
+            PhysicalType::Int32 => i128::from(i32::decode(data)),
+            PhysicalType::Int64 => i128::from(i64::decode(data)),
+            _ => i128::decode(data),
+        }
+    }
+}
+
+/// Formats an unscaled decimal value with the scale, for example `12345` with scale 2 formats
+/// as `123.45`.
+pub fn decimal_to_string(unscaled: i128, scale: u8) -> String {
+    let scale = scale as usize;
+    let magnitude = if unscaled < 0 {
+        (unscaled as u128).wrapping_neg()
+    } else {
+        unscaled as u128
+    };
+    let mut digits = magnitude.to_string();
+    if digits.len() <= scale {
+        digits = "0".repeat(scale + 1 - digits.len()) + &digits;
+    }
+    let sign = if unscaled < 0 { "-" } else { "" };
+    if scale == 0 {
+        format!("{}{}", sign, digits)
+    } else {
+        let (integer, fraction) = digits.split_at(digits.len() - scale);
+        format!("{}{}.{}", sign, integer, fraction)
+    }
+}
+
+/// Parses a decimal string to its unscaled value with the scale, for example `123.45` with scale 2
+/// parses as `12345`. Returns `None` if the string is not a decimal number, has more fractional
+/// digits than the scale, or overflows.
+pub fn decimal_from_string(s: &str, scale: u8) -> Option<i128> {
+    let scale = scale as usize;
+    let (is_negative, digits) = if s.starts_with('-') {
+        (true, &s[1..])
+    } else {
+        (false, s)
+    };
+    let (integer, fraction) = match digits.find('.') {
+        Some(idx) => (&digits[..idx], &digits[idx + 1..]),
+        None => (digits, ""),
+    };
+    if integer.is_empty()
+        || fraction.len() > scale
+        || !integer.bytes().chain(fraction.bytes()).all(|b| b.is_ascii_digit())
+    {
+        return None;
+    }
+
+    let padding = iter::repeat(b'0').take(scale - fraction.len());
+    let mut unscaled = 0i128;
+    for digit in integer.bytes().chain(fraction.bytes()).chain(padding) {
+        unscaled = unscaled
+            .checked_mul(10)?
+            .checked_add(i128::from(digit - b'0'))?;
+    }
+    Some(if is_negative { -unscaled } else { unscaled })
+}
+
 pub fn fmt_cell(f: &mut fmt::Formatter, row: &Row, idx: usize) -> fmt::Result {
     debug_assert!(row.is_set(idx).unwrap());
     if row.is_null(idx).unwrap() {
@@ -75,6 +156,14 @@ pub fn fmt_cell(f: &mut fmt::Formatter, row: &Row, idx: usize) -> fmt::Result {
         DataType::Double => write!(f, "{}", row.get::<_, f64>(idx).unwrap()),
         DataType::Binary => fmt_hex(f, row.get::<_, &[u8]>(idx).unwrap()),
         DataType::String => write!(f, "{:?}", row.get::<_, &str>(idx).unwrap()),
+        DataType::Decimal32 | DataType::Decimal64 | DataType::Decimal128 => {
+            let scale = row.schema().columns()[idx].scale().unwrap();
+            write!(
+                f,
+                "{}",
+                decimal_to_string(decimal_cell(row, idx).unwrap(), scale)
+            )
+        }
     }
 }
 
@@ -96,7 +185,6 @@ lazy_static! {
 }
 
 /// Returns `true` if socket addr is for a local interface.
-#[allow(dead_code)]
 pub fn is_local_addr(addr: &IpAddr) -> bool {
     LOCAL_ADDRS.contains(addr) || addr.is_loopback()
 }
@@ -160,6 +248,30 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn test_decimal_to_string() {
+        assert_eq!("123.45", decimal_to_string(12345, 2));
+        assert_eq!("-123.45", decimal_to_string(-12345, 2));
+        assert_eq!("0.05", decimal_to_string(5, 2));
+        assert_eq!("-0.005", decimal_to_string(-5, 3));
+        assert_eq!("42", decimal_to_string(42, 0));
+        assert_eq!("0", decimal_to_string(0, 0));
+    }
+
+    #[test]
+    fn test_decimal_from_string() {
+        assert_eq!(Some(12345), decimal_from_string("123.45", 2));
+        assert_eq!(Some(-12345), decimal_from_string("-123.45", 2));
+        assert_eq!(Some(500), decimal_from_string("5", 2));
+        assert_eq!(Some(-5), decimal_from_string("-0.005", 3));
+        assert_eq!(Some(42), decimal_from_string("42", 0));
+        assert_eq!(None, decimal_from_string("0.005", 2));
+        assert_eq!(None, decimal_from_string(".5", 2));
+        assert_eq!(None, decimal_from_string("1e5", 2));
+        assert_eq!(None, decimal_from_string("", 0));
+        assert_eq!(None, decimal_from_string(&"9".repeat(40), 0));
+    }
+
     #[test]
     fn test_is_local_addr() {
         let _ = env_logger::try_init();
@@ -15,10 +15,15 @@ use Result;
 ///
 /// Idiomatic Rust types which correspond to the Kudu column types implement `Value`, for example
 /// Kudu's 8 byte integer type maps to a Rust `i64`, Kudu's string type maps to `&str` and
-/// `String`, and Kudu's timestamp type maps to [[SystemTime]].
+/// `String`, and Kudu's timestamp type maps to [[SystemTime]]. Decimal values map to their
+/// unscaled integer values: `i32` for `Decimal32`, `i64` for `Decimal64`, and `i128` for
+/// `Decimal128` columns.
 ///
 /// Inline vs Cell format.
-pub trait Value<'data>: Sized {
+///
+/// `Value` is sealed, since its implementations read and write raw row data; it can not be
+/// implemented outside of this crate.
+pub trait Value<'data>: private::Sealed + Sized {
     /// The Kudu column type corresponding to the value type.
     const DATA_TYPE: DataType;
     const PHYSICAL_TYPE: PhysicalType;
@@ -54,6 +59,13 @@ pub trait Value<'data>: Sized {
         !self.is_null()
     }
 
+    /// Returns the value as an unscaled decimal, or `None` if the value is NULL or is not an
+    /// integer.
+    #[inline]
+    fn to_unscaled(&self) -> Option<i128> {
+        None
+    }
+
     /// Creates a null value, or `None` if the value type is not nullable.
     #[inline]
     fn null() -> Option<Self> {
@@ -92,6 +104,28 @@ pub trait Value<'data>: Sized {
     unsafe fn write_cell(self, *mut u8);
 }
 
+mod private {
+    use std::time::SystemTime;
+
+    /// Prevents `Value` from being implemented outside of this crate.
+    pub trait Sealed {}
+
+    impl Sealed for bool {}
+    impl Sealed for i8 {}
+    impl Sealed for i16 {}
+    impl Sealed for i32 {}
+    impl Sealed for i64 {}
+    impl Sealed for i128 {}
+    impl Sealed for f32 {}
+    impl Sealed for f64 {}
+    impl Sealed for SystemTime {}
+    impl<'data> Sealed for &'data [u8] {}
+    impl Sealed for Vec<u8> {}
+    impl<'data> Sealed for &'data str {}
+    impl Sealed for String {}
+    impl<V> Sealed for Option<V> where V: Sealed {}
+}
+
 impl<'data> Value<'data> for bool {
     const DATA_TYPE: DataType = DataType::Bool;
     const PHYSICAL_TYPE: PhysicalType = PhysicalType::Bool;
@@ -111,10 +145,13 @@ impl<'data> Value<'data> for bool {
 }
 
 macro_rules! int_value {
-    ($ty:ty, $data_type:ident) => {
+    ($ty:ty, $data_type:ident, $physical_type:ident) => {
         impl<'data> Value<'data> for $ty {
             const DATA_TYPE: DataType = DataType::$data_type;
-            const PHYSICAL_TYPE: PhysicalType = PhysicalType::$data_type;
+            const PHYSICAL_TYPE: PhysicalType = PhysicalType::$physical_type;
+            fn to_unscaled(&self) -> Option<i128> {
+                Some(i128::from(*self))
+            }
             #[cfg_attr(feature = "cargo-clippy", allow(cast_ptr_alignment))]
             unsafe fn read_cell(data: *const u8) -> Result<$ty> {
                 Ok((data as *const $ty).read_unaligned().to_le())
@@ -129,10 +166,11 @@ macro_rules! int_value {
         }
     };
 }
-int_value!(i8, Int8);
-int_value!(i16, Int16);
-int_value!(i32, Int32);
-int_value!(i64, Int64);
+int_value!(i8, Int8, Int8);
+int_value!(i16, Int16, Int16);
+int_value!(i32, Int32, Int32);
+int_value!(i64, Int64, Int64);
+int_value!(i128, Decimal128, Int128);
 
 macro_rules! float_value {
     ($ty:ident, $data_type:ident, $unsigned_ty:ty) => {
@@ -174,9 +212,16 @@ impl<'data> Value<'data> for SystemTime {
     unsafe fn write_cell(self, data: *mut u8) {
         time_to_us(self).write_cell(data);
     }
+    // Timestamps are encoded as microseconds, which are smaller than `SystemTime`.
+    fn encode(self) -> Vec<u8> {
+        time_to_us(self).encode()
+    }
     fn encode_next(self) -> Option<Vec<u8>> {
         time_to_us(self).encode_next()
     }
+    unsafe fn decode(data: &'data [u8]) -> SystemTime {
+        us_to_time(i64::decode(data))
+    }
 }
 
 impl<'data> Value<'data> for &'data [u8] {
@@ -317,6 +362,9 @@ where
     fn is_comparable(&self) -> bool {
         self.as_ref().map_or(false, V::is_comparable)
     }
+    fn to_unscaled(&self) -> Option<i128> {
+        self.as_ref().and_then(V::to_unscaled)
+    }
     unsafe fn read_cell(data: *const u8) -> Result<Option<V>> {
         Ok(Some(V::read_cell(data)?))
     }
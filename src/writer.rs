@@ -9,9 +9,9 @@ use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use futures::stream::{FuturesOrdered, FuturesUnordered};
-use futures::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
 use futures::{future, Async, Future, Poll, Sink, Stream};
 use krpc::Call;
+use parking_lot::Mutex;
 
 use backoff::Backoff;
 use key;
@@ -19,6 +19,7 @@ use operation::{Operation, OperationDecoder, OperationEncoder, OperationError, O
 use partition::PartitionKey;
 use pb::tserver::{TabletServerService, WriteRequestPb, WriteResponsePb};
 use replica::{Replica, ReplicaRpc, ReplicaSet, Selection, Speculation};
+use request_tracker::Request;
 use tablet::Tablet;
 use tokio_timer::Delay;
 use Client;
@@ -26,8 +27,10 @@ use Error;
 use PartitionSchema;
 use Row;
 use Schema;
+use Status;
 use Table;
 use TabletId;
+use TabletServerErrorCode;
 
 #[derive(Debug, Clone)]
 pub struct WriterConfig {
@@ -35,6 +38,10 @@ pub struct WriterConfig {
     /// server. If the timeout expires before the batch completes, the operations will fail with
     /// `Error::TimedOut`.
     ///
+    /// Batches which fail with a retriable error, such as a leader election in progress, are
+    /// retried until the timeout expires. Batches sent to a tablet which no longer exists at the
+    /// cached location are re-routed after the tablet locations are looked up again.
+    ///
     /// Defaults to 120 seconds.
     flush_timeout: Duration,
 
@@ -52,6 +59,15 @@ pub struct WriterConfig {
     /// Defaults to 7MiB.
     max_data_per_batch: usize,
 
+    /// Maximum amount of time that a partially filled batch is buffered before it is
+    /// automatically dispatched to the tablet server, or `None` if batches are only dispatched
+    /// when they become full or when the writer is flushed. The task which applies an operation
+    /// to an empty batch is notified when the interval elapses, and the batch is dispatched when
+    /// the task next polls the writer with `Writer::poll_ready`.
+    ///
+    /// Defaults to `None`.
+    flush_interval: Option<Duration>,
+
     /// Maximum number of concurrent in-flight batches per tablet.
     ///
     /// Defaults to 2. Must be at least 1.
@@ -63,6 +79,25 @@ pub struct WriterConfig {
     ///
     /// Defaults to 80. Must be between 0 (exclusive) and 100 (inclusive).
     early_flush_watermark: u8,
+
+    /// Maximum number of failed row operations to buffer until they are taken with
+    /// `Writer::take_errors`. Additional errors are discarded, and the taken errors are marked as
+    /// overflowed.
+    ///
+    /// Defaults to 1000.
+    max_buffered_errors: usize,
+}
+
+impl WriterConfig {
+    pub fn flush_interval(&mut self, flush_interval: Option<Duration>) -> &mut WriterConfig {
+        self.flush_interval = flush_interval;
+        self
+    }
+
+    pub fn max_buffered_errors(&mut self, max_buffered_errors: usize) -> &mut WriterConfig {
+        self.max_buffered_errors = max_buffered_errors;
+        self
+    }
 }
 
 impl Default for WriterConfig {
@@ -71,8 +106,10 @@ impl Default for WriterConfig {
             flush_timeout: Duration::from_secs(120),
             max_buffered_data: 256 * 1024 * 1024,
             max_data_per_batch: 7 * 1024 * 1024,
+            flush_interval: None,
             max_batches_per_tablet: 2,
             early_flush_watermark: 80,
+            max_buffered_errors: 1000,
         }
     }
 }
@@ -82,7 +119,7 @@ pub struct Writer {
         Box<
             Future<
                     Item = (Option<Arc<Tablet>>, Operation<'static>, usize),
-                    Error = (Operation<'static>, Error),
+                    Error = (Operation<'static>, usize, Error),
                 > + Send,
         >,
     >,
@@ -96,6 +133,10 @@ pub struct Writer {
     /// Stats for current flush.
     flush_stats: FlushStats,
 
+    /// Expires when the oldest open batch reaches the flush interval. Set only if there are open
+    /// batches.
+    flush_timer: Option<Delay>,
+
     common: Common,
 }
 
@@ -105,25 +146,23 @@ struct Common {
 
     batches_in_flight: FuturesUnordered<Box<Future<Item = BatchStats, Error = BatchError> + Send>>,
 
-    error_sender: UnboundedSender<OperationError>,
-    #[allow(dead_code)] // TODO: expose this in a nice API so applications can handle row errors.
-    error_receiver: UnboundedReceiver<OperationError>,
+    errors: Arc<Mutex<ErrorCollector>>,
 }
 
 impl Writer {
     pub(crate) fn new(table: Table, config: WriterConfig) -> Writer {
-        let (error_sender, error_receiver) = mpsc::unbounded();
+        let errors = Arc::new(Mutex::new(ErrorCollector::new(config.max_buffered_errors)));
         Writer {
             operations_in_lookup: FuturesOrdered::new(),
             batchers: HashMap::new(),
             buffered_data: 0,
             flush_stats: FlushStats::new(),
+            flush_timer: None,
             common: Common {
                 config,
                 table,
                 batches_in_flight: FuturesUnordered::new(),
-                error_sender,
-                error_receiver,
+                errors,
             },
         }
     }
@@ -133,6 +172,8 @@ impl Writer {
 
         self.poll_batches_in_flight(false)?;
 
+        self.poll_flush_timer();
+
         // TODO: figure out if the amount of data in batches is over the early flush watermark.
 
         if self.buffered_data >= self.common.config.max_buffered_data {
@@ -163,6 +204,11 @@ impl Writer {
         }
     }
 
+    /// Takes the row operations which have failed since the errors were last taken.
+    pub fn take_errors(&mut self) -> RowErrors {
+        self.common.errors.lock().take()
+    }
+
     pub fn flush(self) -> Flush {
         Flush { writer: Some(self) }
     }
@@ -171,6 +217,7 @@ impl Writer {
         if op.row.schema() != self.common.table.schema() {
             self.fail_operation(
                 op,
+                0,
                 Error::InvalidArgument(
                     "row operation schema does not match the writer schema".to_owned(),
                 ),
@@ -185,6 +232,7 @@ impl Writer {
         if encoded_len > self.common.config.max_data_per_batch {
             self.fail_operation(
                 op,
+                0,
                 Error::InvalidArgument(
                     "row operation size is greater than the max batch size".to_owned(),
                 ),
@@ -200,7 +248,7 @@ impl Writer {
             match key::encode_partition_key(self.common.table.partition_schema(), &op.row) {
                 Ok(partition_key) => partition_key,
                 Err(error) => {
-                    self.fail_operation(op, error);
+                    self.fail_operation(op, encoded_len, error);
                     return;
                 }
             };
@@ -214,16 +262,18 @@ impl Writer {
 
         match poll {
             Ok(Async::Ready(Some(tablet))) => self.buffer_operation(tablet, &op, encoded_len),
-            Ok(Async::Ready(None)) => self.fail_operation(op, Error::NoRangePartition),
+            Ok(Async::Ready(None)) => {
+                self.fail_operation(op, encoded_len, Error::NoRangePartition)
+            }
             Ok(Async::NotReady) => {
                 let op = op.into_owned();
                 let operation_in_lookup = Box::new(tablet.then(move |result| match result {
                     Ok(tablet) => Ok((tablet, op, encoded_len)),
-                    Err(error) => Err((op, error)),
+                    Err(error) => Err((op, encoded_len, error)),
                 }));
                 self.operations_in_lookup.push(operation_in_lookup);
             }
-            Err(error) => self.fail_operation(op, error),
+            Err(error) => self.fail_operation(op, encoded_len, error),
         }
     }
 
@@ -292,18 +342,96 @@ impl Writer {
         }
     }
 
+    pub fn upsert(&mut self, row: Row) {
+        self.apply(Operation {
+            row,
+            kind: OperationKind::Upsert,
+        })
+    }
+
+    pub fn upsert_all<'data, I>(self, rows: I) -> WriteAll<I::IntoIter>
+    where
+        I: IntoIterator<Item = Row<'data>>,
+    {
+        WriteAll {
+            items: rows.into_iter(),
+            f: Writer::upsert,
+            writer: Some(self),
+        }
+    }
+
+    /// Inserts the row, unless a row with the same primary key already exists. The existing row
+    /// is not changed, and is not reported as a row error.
+    pub fn insert_ignore(&mut self, row: Row) {
+        self.apply(Operation {
+            row,
+            kind: OperationKind::InsertIgnore,
+        })
+    }
+
+    pub fn insert_ignore_all<'data, I>(self, rows: I) -> WriteAll<I::IntoIter>
+    where
+        I: IntoIterator<Item = Row<'data>>,
+    {
+        WriteAll {
+            items: rows.into_iter(),
+            f: Writer::insert_ignore,
+            writer: Some(self),
+        }
+    }
+
+    /// Updates the row if it exists. Updates of rows which do not exist are not reported as row
+    /// errors.
+    pub fn update_ignore(&mut self, row: Row) {
+        self.apply(Operation {
+            row,
+            kind: OperationKind::UpdateIgnore,
+        })
+    }
+
+    pub fn update_ignore_all<'data, I>(self, rows: I) -> WriteAll<I::IntoIter>
+    where
+        I: IntoIterator<Item = Row<'data>>,
+    {
+        WriteAll {
+            items: rows.into_iter(),
+            f: Writer::update_ignore,
+            writer: Some(self),
+        }
+    }
+
+    /// Deletes the row if it exists. Deletes of rows which do not exist are not reported as row
+    /// errors.
+    pub fn delete_ignore(&mut self, row: Row) {
+        self.apply(Operation {
+            row,
+            kind: OperationKind::DeleteIgnore,
+        })
+    }
+
+    pub fn delete_ignore_all<'data, I>(self, rows: I) -> WriteAll<I::IntoIter>
+    where
+        I: IntoIterator<Item = Row<'data>>,
+    {
+        WriteAll {
+            items: rows.into_iter(),
+            f: Writer::delete_ignore,
+            writer: Some(self),
+        }
+    }
+
     fn poll_operations_in_lookup(&mut self) -> Poll<(), Error> {
         loop {
             match self.operations_in_lookup.poll() {
                 Ok(Async::Ready(Some((Some(tablet), op, encoded_len)))) => {
                     self.buffer_operation(tablet, &op, encoded_len)
                 }
-                Ok(Async::Ready(Some((None, op, _)))) => {
-                    self.fail_operation(op, Error::NoRangePartition)
+                Ok(Async::Ready(Some((None, op, encoded_len)))) => {
+                    self.fail_operation(op, encoded_len, Error::NoRangePartition)
                 }
                 Ok(Async::NotReady) => return Ok(Async::NotReady),
                 Ok(Async::Ready(None)) => return Ok(Async::Ready(())),
-                Err((op, error)) => self.fail_operation(op, error),
+                Err((op, encoded_len, error)) => self.fail_operation(op, encoded_len, error),
             }
         }
     }
@@ -319,31 +447,186 @@ impl Writer {
                 Ok(Async::Ready(Some(stats))) => {
                     self.buffered_data -= stats.data;
                     self.flush_stats.add_succesful_batch(&stats);
-
-                    match self.batchers.entry(stats.tablet) {
-                        Entry::Occupied(ref mut entry) => {
-                            entry.get_mut().batches_in_flight -= 1;
-                            if flush_batches {
-                                entry.get_mut().flush(&mut self.common, false);
-                            } else {
-                                entry.get_mut().send_batches(&mut self.common);
-                            }
-                        }
-                        Entry::Vacant(..) => unreachable!("unknown batch tablet"),
-                    }
+                    self.finish_batch(stats.tablet, flush_batches);
                 }
                 Ok(Async::NotReady) => return Ok(Async::NotReady),
                 Ok(Async::Ready(None)) => return Ok(Async::Ready(())),
-                Err(BatchError { call, stats, error }) => {
-                    // TODO: handle recoverable errors here.
+                Err(BatchError {
+                    tablet,
+                    mut call,
+                    mut request,
+                    kinds,
+                    stats,
+                    mut backoff,
+                    error,
+                }) => {
+                    let retry = Instant::now() + backoff.next_backoff();
+                    if retry < call.deadline() {
+                        if is_tablet_failure(&error) {
+                            // The tablet no longer exists at the cached location. Look up the
+                            // tablet locations again, and re-apply the operations.
+                            debug!(
+                                "{:?}: re-routing batch; tablet: {:?}, error: {}",
+                                self,
+                                tablet,
+                                error
+                            );
+                            // The batch is abandoned, so its sequence number is complete.
+                            drop(request);
+                            self.buffered_data -= stats.data;
+                            self.reroute_batches(&tablet, &call, &kinds, flush_batches);
+                            continue;
+                        } else if is_retriable_batch_failure(&error) {
+                            debug!(
+                                "{:?}: retrying batch; tablet: {:?}, error: {}",
+                                self,
+                                tablet,
+                                error
+                            );
+                            // Retries keep the sequence number of the request ID, so that the batch
+                            // is applied at most once.
+                            call.set_request_id(request.retry().clone());
+                            let table = self.common.table.clone();
+                            let errors = self.common.errors.clone();
+                            let batch = Delay::new(retry).then(move |result| {
+                                result.expect("timer failed");
+                                write_batch(
+                                    &table, &errors, tablet, call, request, kinds, stats, backoff,
+                                )
+                            });
+                            self.common.batches_in_flight.push(Box::new(batch));
+                            continue;
+                        }
+                    }
+
+                    drop(request);
                     self.buffered_data -= stats.data;
                     self.flush_stats.failed_batches += 1;
+                    self.finish_batch(stats.tablet, flush_batches);
                     return Err(error);
                 }
             };
         }
     }
 
+    /// Updates the tablet batcher after a batch completes, and sends the next batches.
+    fn finish_batch(&mut self, tablet: TabletId, flush_batches: bool) {
+        match self.batchers.entry(tablet) {
+            Entry::Occupied(ref mut entry) => {
+                entry.get_mut().batches_in_flight -= 1;
+                if flush_batches {
+                    entry.get_mut().flush(&mut self.common, false);
+                } else {
+                    entry.get_mut().send_batches(&mut self.common);
+                }
+            }
+            Entry::Vacant(..) => unreachable!("unknown batch tablet"),
+        }
+    }
+
+    /// Re-routes the operations of a batch which failed because the tablet no longer exists at the
+    /// cached location, along with the operations of the tablet's unsent batches.
+    fn reroute_batches(
+        &mut self,
+        tablet: &Tablet,
+        call: &Call<WriteRequestPb, WriteResponsePb>,
+        kinds: &[OperationKind],
+        flush_batches: bool,
+    ) {
+        self.common
+            .table
+            .table_locations()
+            .invalidate_tablet(tablet);
+
+        let mut batches = Vec::new();
+        if let Some(batcher) = self.batchers.get_mut(&tablet.id()) {
+            batcher.batches_in_flight -= 1;
+            batches.extend(batcher.batch_queue.drain(..));
+            batches.push(mem::replace(&mut batcher.batch, Batch::new()));
+        }
+
+        let schema = self.common.table.schema().clone();
+        let row_operations = call.request.row_operations.as_ref().unwrap();
+        self.reapply(
+            &schema,
+            row_operations.rows(),
+            row_operations.indirect_data(),
+            kinds,
+        );
+        for batch in batches {
+            self.buffered_data -= batch.encoder.len();
+            self.reapply(
+                &schema,
+                &batch.encoder.data,
+                &batch.encoder.indirect_data,
+                &batch.kinds,
+            );
+        }
+
+        // Poll the lookups of the re-applied operations in order to be notified when they complete.
+        let _ = self.poll_operations_in_lookup();
+        if flush_batches {
+            for batcher in self.batchers.values_mut() {
+                batcher.flush(&mut self.common, false);
+            }
+        }
+    }
+
+    /// Applies encoded operations again.
+    fn reapply(
+        &mut self,
+        schema: &Schema,
+        rows: &[u8],
+        indirect_data: &[u8],
+        kinds: &[OperationKind],
+    ) {
+        let decoder = OperationDecoder::new(schema, rows, indirect_data);
+        for (mut operation, &kind) in decoder.zip(kinds) {
+            // The encoded operations do not distinguish the ignore operation kinds.
+            operation.kind = kind;
+            self.apply(operation);
+        }
+    }
+
+    /// Dispatches the batches which have been open for longer than the flush interval, and resets
+    /// the flush timer to expire when the oldest remaining batch reaches the flush interval.
+    fn poll_flush_timer(&mut self) {
+        let flush_interval = match self.common.config.flush_interval {
+            Some(flush_interval) => flush_interval,
+            None => return,
+        };
+        loop {
+            let expired = match self.flush_timer {
+                Some(ref mut timer) => timer.poll().expect("timer failed").is_ready(),
+                None => false,
+            };
+            if !expired {
+                return;
+            }
+
+            let now = Instant::now();
+            let mut next_flush = None;
+            for batcher in self.batchers.values_mut() {
+                if batcher.batch.is_empty() {
+                    continue;
+                }
+                let flush = batcher.batch.opened + flush_interval;
+                if flush <= now {
+                    // Force the batch into the queue, since the tablet may have the maximum
+                    // number of batches in flight. It is sent once an in-flight batch completes.
+                    batcher.flush(&mut self.common, true);
+                } else if next_flush.map_or(true, |next_flush| flush < next_flush) {
+                    next_flush = Some(flush);
+                }
+            }
+
+            match next_flush {
+                Some(next_flush) => self.flush_timer.as_mut().unwrap().reset(next_flush),
+                None => self.flush_timer = None,
+            }
+        }
+    }
+
     /// Applies an operation to the appropriate tablet batch.
     fn buffer_operation(&mut self, tablet: Arc<Tablet>, op: &Operation, encoded_len: usize) {
         trace!(
@@ -364,12 +647,27 @@ impl Writer {
         if batcher.batch.encoder.len() + encoded_len > self.common.config.max_data_per_batch {
             batcher.flush(&mut self.common, true);
         }
+        if batcher.batch.is_empty() {
+            batcher.batch.opened = Instant::now();
+            if let Some(flush_interval) = self.common.config.flush_interval {
+                if self.flush_timer.is_none() {
+                    let mut timer = Delay::new(batcher.batch.opened + flush_interval);
+                    // Poll the timer so that the current task is notified when it expires, since
+                    // the writer may not be polled again until then. The expiration is handled by
+                    // `poll_flush_timer`.
+                    let _ = timer.poll();
+                    self.flush_timer = Some(timer);
+                }
+            }
+        }
         batcher.batch.encoder.encode_row(op.kind.as_pb(), &op.row);
-        batcher.batch.operations += 1;
+        batcher.batch.kinds.push(op.kind);
     }
 
-    fn fail_operation(&self, operation: Operation, error: Error) {
-        let _ = self.common.error_sender.unbounded_send(OperationError {
+    /// Fails an operation, and releases the operation's buffered data, if any.
+    fn fail_operation(&mut self, operation: Operation, encoded_len: usize, error: Error) {
+        self.buffered_data -= encoded_len;
+        self.common.errors.lock().push(OperationError {
             row: operation.row.into_owned(),
             kind: operation.kind,
             error,
@@ -497,101 +795,153 @@ impl fmt::Debug for TabletBatcher {
 
 struct Batch {
     encoder: OperationEncoder,
-    operations: usize,
+    /// The kind of each operation in the batch.
+    kinds: Vec<OperationKind>,
+    /// The time the first operation was applied to the batch.
+    opened: Instant,
 }
 
 impl Batch {
     fn new() -> Batch {
         Batch {
             encoder: OperationEncoder::new(),
-            operations: 0,
+            kinds: Vec::new(),
+            opened: Instant::now(),
         }
     }
 
     fn is_empty(&self) -> bool {
-        self.operations == 0
+        self.kinds.is_empty()
     }
 
     fn send(self, tablet: Arc<Tablet>, common: &mut Common) {
-        let mut stats = BatchStats {
+        let stats = BatchStats {
             tablet: tablet.id(),
-            operations: self.operations,
+            operations: self.kinds.len(),
             row_errors: 0,
+            ignored_row_errors: 0,
             data: self.encoder.len(),
         };
 
         let mut request = WriteRequestPb::default();
         request.tablet_id = tablet.id().to_string().into_bytes();
         request.schema = Some(common.table.schema().as_pb());
-        //request.propagated_timestamp = Some(self.client().latest_observed_timestamp());
+        let client = common.table.client().clone();
+        let timestamp = client.latest_observed_timestamp();
+        if timestamp > 0 {
+            request.propagated_timestamp = Some(timestamp);
+        }
         request.row_operations = Some(self.encoder.into_pb());
-        let call1 = TabletServerService::write(
+        let mut call = TabletServerService::write(
             Arc::new(request),
             Instant::now() + common.config.flush_timeout,
         );
-        let call2 = call1.clone();
-        let call3 = call1.clone();
-
-        let schema = common.table.schema().clone();
-        let error_sender = common.error_sender.clone();
-
-        common.batches_in_flight.push(Box::new(
-            ReplicaRpc::new(
-                tablet,
-                call1,
-                Speculation::Staggered(Duration::from_millis(100)),
-                Selection::Leader,
-                Backoff::default(),
-            ).and_then(move |(_, response, _)| {
-                assert!(response.error.is_none());
-                let row_errors = response.per_row_errors.len();
-                if row_errors != 0 {
-                    debug!("row_errors: {:?}", response.per_row_errors);
-                    let row_operations = call2.request.row_operations.as_ref().unwrap();
-                    let mut decoder = OperationDecoder::new(
-                        &schema,
-                        row_operations.rows(),
-                        row_operations.indirect_data(),
-                    );
-                    let mut decoder_idx = 0;
+        let request = client.request_tracker().new_request();
+        call.set_request_id(request.id().clone());
 
-                    for error in response.per_row_errors {
-                        if error.row_index < 0 || error.row_index as usize >= stats.operations {
-                            return Err(Error::Serialization(format!(
-                                "row error contains invalid index: {:?}",
-                                error
-                            )));
-                        }
-                        let error_idx = error.row_index as usize;
-                        if error_idx < decoder_idx {
-                            return Err(Error::Serialization("out-of-order row error".to_string()));
-                        }
-
-                        let operation = decoder.nth(error_idx - decoder_idx).unwrap();
-                        decoder_idx = error_idx + 1;
+        let batch = write_batch(
+            &common.table,
+            &common.errors,
+            tablet,
+            call,
+            request,
+            Arc::new(self.kinds),
+            stats,
+            Backoff::default(),
+        );
+        common.batches_in_flight.push(batch);
+    }
+}
 
-                        let error = OperationError {
-                            row: operation.row.into_owned(),
-                            kind: operation.kind,
-                            error: Error::RowError(error.error.into()),
-                        };
+/// Sends a batch to the leader replica of the tablet, and collects the failed row operations.
+///
+/// The batch future owns the request, so the sequence number of the request is completed when the
+/// future completes or is dropped.
+#[cfg_attr(feature = "cargo-clippy", allow(too_many_arguments))]
+fn write_batch(
+    table: &Table,
+    errors: &Arc<Mutex<ErrorCollector>>,
+    tablet: Arc<Tablet>,
+    call: Call<WriteRequestPb, WriteResponsePb>,
+    request: Request,
+    kinds: Arc<Vec<OperationKind>>,
+    mut stats: BatchStats,
+    backoff: Backoff,
+) -> Box<Future<Item = BatchStats, Error = BatchError> + Send> {
+    let call2 = call.clone();
+    let call3 = call.clone();
+    let tablet2 = tablet.clone();
+    let kinds2 = kinds.clone();
+
+    let client = table.client().clone();
+    let schema = table.schema().clone();
+    let errors = errors.clone();
+
+    Box::new(
+        ReplicaRpc::new(
+            tablet,
+            call,
+            Speculation::Staggered(Duration::from_millis(100)),
+            Selection::Leader,
+            backoff.clone(),
+        ).and_then(move |(_, response, _)| {
+            assert!(response.error.is_none());
+            if let Some(timestamp) = response.timestamp {
+                client.observe_timestamp(timestamp);
+            }
+            if !response.per_row_errors.is_empty() {
+                debug!("row_errors: {:?}", response.per_row_errors);
+                let row_operations = call2.request.row_operations.as_ref().unwrap();
+                let mut decoder = OperationDecoder::new(
+                    &schema,
+                    row_operations.rows(),
+                    row_operations.indirect_data(),
+                );
+                let mut decoder_idx = 0;
+                let mut errors = errors.lock();
+
+                for error in response.per_row_errors {
+                    if error.row_index < 0 || error.row_index as usize >= stats.operations {
+                        return Err(Error::Serialization(format!(
+                            "row error contains invalid index: {:?}",
+                            error
+                        )));
+                    }
+                    let error_idx = error.row_index as usize;
+                    if error_idx < decoder_idx {
+                        return Err(Error::Serialization("out-of-order row error".to_string()));
+                    }
 
-                        if error_sender.unbounded_send(error).is_err() {
-                            // The receiver has been dropped, so no point in sending additional errors.
-                            break;
-                        }
+                    let status = Status::from(error.error);
+                    let kind = kinds[error_idx];
+                    if kind.ignores(status.code()) {
+                        stats.ignored_row_errors += 1;
+                        continue;
                     }
+
+                    let operation = decoder.nth(error_idx - decoder_idx).unwrap();
+                    decoder_idx = error_idx + 1;
+
+                    stats.row_errors += 1;
+                    errors.push(OperationError {
+                        row: operation.row.into_owned(),
+                        kind,
+                        error: Error::RowError(status),
+                    });
                 }
+            }
 
-                stats.row_errors = row_errors;
-                Ok(stats)
-            }).map_err(move |error| BatchError {
-                call: call3,
-                stats,
-                error,
-            }),
-        ));
-    }
+            Ok(stats)
+        }).map_err(move |error| BatchError {
+            tablet: tablet2,
+            call: call3,
+            request,
+            kinds: kinds2,
+            stats,
+            backoff,
+            error,
+        }),
+    )
 }
 
 #[derive(Clone, Copy)]
@@ -599,15 +949,120 @@ struct BatchStats {
     tablet: TabletId,
     operations: usize,
     row_errors: usize,
+    ignored_row_errors: usize,
     data: usize,
 }
 
 struct BatchError {
+    tablet: Arc<Tablet>,
     call: Call<WriteRequestPb, WriteResponsePb>,
+    request: Request,
+    kinds: Arc<Vec<OperationKind>>,
     stats: BatchStats,
+    backoff: Backoff,
     error: Error,
 }
 
+/// Returns true if a batch failed because the tablet does not exist at the location in the meta
+/// cache, for instance because the tablet has been deleted.
+fn is_tablet_failure(error: &Error) -> bool {
+    match *error {
+        Error::TabletServer(ref error) => match error.code {
+            TabletServerErrorCode::TabletNotFound | TabletServerErrorCode::TabletFailed => true,
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Returns true if a failed batch may succeed if it is sent again, for instance after a leader
+/// election or once a busy tablet server catches up.
+fn is_retriable_batch_failure(error: &Error) -> bool {
+    match *error {
+        Error::Io(..) | Error::TimedOut | Error::Compound(..) => true,
+        Error::Rpc(ref error) => error.is_retriable(),
+        Error::TabletServer(ref error) => match error.code {
+            TabletServerErrorCode::NotTheLeader
+            | TabletServerErrorCode::TabletNotRunning
+            | TabletServerErrorCode::Throttled => true,
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Buffers failed row operations until they are taken from the writer, up to a maximum number
+/// of errors.
+struct ErrorCollector {
+    errors: Vec<OperationError>,
+    max_errors: usize,
+    overflowed: bool,
+}
+
+impl ErrorCollector {
+    fn new(max_errors: usize) -> ErrorCollector {
+        ErrorCollector {
+            errors: Vec::new(),
+            max_errors,
+            overflowed: false,
+        }
+    }
+
+    fn push(&mut self, error: OperationError) {
+        if self.errors.len() < self.max_errors {
+            self.errors.push(error);
+        } else {
+            self.overflowed = true;
+        }
+    }
+
+    fn take(&mut self) -> RowErrors {
+        RowErrors {
+            errors: mem::replace(&mut self.errors, Vec::new()),
+            overflowed: mem::replace(&mut self.overflowed, false),
+        }
+    }
+}
+
+/// Failed row operations taken from a writer with `Writer::take_errors`.
+#[derive(Debug)]
+pub struct RowErrors {
+    errors: Vec<OperationError>,
+    overflowed: bool,
+}
+
+impl RowErrors {
+    pub fn errors(&self) -> &[OperationError] {
+        &self.errors
+    }
+
+    pub fn into_errors(self) -> Vec<OperationError> {
+        self.errors
+    }
+
+    /// Returns `true` if errors were discarded because more than `max_buffered_errors` errors
+    /// were buffered by the writer.
+    pub fn is_overflowed(&self) -> bool {
+        self.overflowed
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.errors.len()
+    }
+}
+
+impl IntoIterator for RowErrors {
+    type Item = OperationError;
+    type IntoIter = ::std::vec::IntoIter<OperationError>;
+    fn into_iter(self) -> ::std::vec::IntoIter<OperationError> {
+        self.errors.into_iter()
+    }
+}
+
 /// Carries information about the batches and row operations in a flush.
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct FlushStats {
@@ -615,6 +1070,7 @@ pub struct FlushStats {
     failed_batches: usize,
     operations: usize,
     row_errors: usize,
+    ignored_row_errors: usize,
     data: usize,
 }
 
@@ -625,6 +1081,7 @@ impl FlushStats {
             failed_batches: 0,
             operations: 0,
             row_errors: 0,
+            ignored_row_errors: 0,
             data: 0,
         }
     }
@@ -653,6 +1110,13 @@ impl FlushStats {
         self.row_errors
     }
 
+    /// Returns the number of row errors which were ignored because of the operation kind, such as
+    /// duplicate key errors of `OperationKind::InsertIgnore` operations. Operations with ignored
+    /// errors are counted as successful.
+    pub fn ignored_row_errors(&self) -> usize {
+        self.ignored_row_errors
+    }
+
     pub fn data(&self) -> usize {
         self.data
     }
@@ -661,6 +1125,7 @@ impl FlushStats {
         self.successful_batches += 1;
         self.operations += batch.operations;
         self.row_errors += batch.row_errors;
+        self.ignored_row_errors += batch.ignored_row_errors;
         self.data += batch.data;
     }
 }
@@ -671,16 +1136,23 @@ mod test {
     use std::time::{Duration, Instant};
 
     use super::*;
-    use mini_cluster::{MiniCluster, MiniClusterConfig};
+    use mini_cluster::{
+        create_table, create_table_with_rows, insert_rows, MiniCluster, MiniClusterConfig,
+    };
+    use AlterTableBuilder;
     use Client;
     use Column;
     use DataType;
     use Options;
+    use RaftRole;
+    use RangePartitionBound;
     use SchemaBuilder;
+    use StatusCode;
     use TableBuilder;
 
     use env_logger;
     use futures::future;
+    use futures::sync::mpsc;
     use tokio::runtime::current_thread::Runtime;
 
     #[test]
@@ -693,23 +1165,15 @@ mod test {
             .block_on(Client::new(cluster.master_addrs(), Options::default()))
             .expect("client");
 
-        let schema = SchemaBuilder::new()
-            .add_column(Column::new("key", DataType::Int32).set_not_null())
-            .add_column(Column::new("val", DataType::Int32))
-            .set_primary_key(vec!["key"])
-            .build()
-            .unwrap();
-
-        let mut table_builder = TableBuilder::new("insert", schema.clone());
-        table_builder.add_hash_partitions(vec!["key"], 4);
-        table_builder.set_num_replicas(1);
-
-        // TODO: don't wait for table creation in order to test retry logic.
-        let table_id = runtime
-            .block_on(client.create_table(table_builder))
-            .unwrap();
-
-        let table = runtime.block_on(client.open_table_by_id(table_id)).unwrap();
+        let table = create_table_with_rows(
+            &mut runtime,
+            &mut client,
+            "insert",
+            |builder| {
+                builder.add_hash_partitions(vec!["key"], 4);
+            },
+            0,
+        );
 
         let mut writer = table.new_writer(WriterConfig::default());
 
@@ -750,5 +1214,358 @@ mod test {
         assert_eq!(stats.operations, 12);
         assert_eq!(stats.row_errors, 1);
         assert_eq!(stats.data, 128);
+        assert!(client.latest_observed_timestamp() > 0);
+
+        let errors = writer.take_errors();
+        assert!(!errors.is_overflowed());
+        assert_eq!(1, errors.len());
+        let error = &errors.errors()[0];
+        assert_eq!(OperationKind::Insert, error.kind);
+        assert_eq!(1, error.row.get::<_, i32>("key").unwrap());
+        assert_eq!(StatusCode::AlreadyPresent, error.status().unwrap().code());
+        assert!(writer.take_errors().is_empty());
+    }
+
+    #[test]
+    fn error_overflow() {
+        let _ = env_logger::try_init();
+        let mut cluster = MiniCluster::default();
+        let mut runtime = Runtime::new().unwrap();
+
+        let mut client = runtime
+            .block_on(Client::new(cluster.master_addrs(), Options::default()))
+            .expect("client");
+
+        let schema = SchemaBuilder::new()
+            .add_column(Column::new("key", DataType::Int32).set_not_null())
+            .set_primary_key(vec!["key"])
+            .build()
+            .unwrap();
+
+        let mut table_builder = TableBuilder::new("error_overflow", schema.clone());
+        table_builder.set_range_partition_columns(vec!["key"]);
+        table_builder.set_num_replicas(1);
+
+        let table = create_table(&mut runtime, &mut client, table_builder);
+
+        let mut config = WriterConfig::default();
+        config.max_buffered_errors(2);
+        let mut writer = table.new_writer(config);
+
+        // TODO: remove lazy once apply no longer polls.
+        runtime
+            .block_on(future::lazy::<_, Result<(), ()>>(|| {
+                // Insert the same key repeatedly.
+                for _ in 0..5 {
+                    let mut insert = table.schema().new_row();
+                    insert.set("key", 0i32).unwrap();
+                    writer.insert(insert);
+                }
+                Ok(())
+            })).unwrap();
+
+        let stats = runtime
+            .block_on(future::poll_fn(|| writer.poll_flush()))
+            .unwrap();
+        assert_eq!(4, stats.failed_operations());
+
+        let errors = writer.take_errors();
+        assert!(errors.is_overflowed());
+        assert_eq!(2, errors.len());
+        assert!(!writer.take_errors().is_overflowed());
+    }
+
+    #[test]
+    fn ignore_errors() {
+        let _ = env_logger::try_init();
+        let mut cluster = MiniCluster::default();
+        let mut runtime = Runtime::new().unwrap();
+
+        let mut client = runtime
+            .block_on(Client::new(cluster.master_addrs(), Options::default()))
+            .expect("client");
+
+        let table = create_table_with_rows(
+            &mut runtime,
+            &mut client,
+            "ignore_errors",
+            |builder| {
+                builder.add_hash_partitions(vec!["key"], 2);
+            },
+            0,
+        );
+        let mut writer = table.new_writer(WriterConfig::default());
+
+        let row = |key: i32| {
+            let mut row = table.schema().new_row();
+            row.set("key", key).unwrap();
+            row.set("val", key).unwrap();
+            row
+        };
+
+        // TODO: remove lazy once apply no longer polls.
+        runtime
+            .block_on(future::lazy::<_, Result<(), ()>>(|| {
+                for i in 0..5 {
+                    writer.insert(row(i));
+                }
+                Ok(())
+            })).unwrap();
+        runtime
+            .block_on(future::poll_fn(|| writer.poll_flush()))
+            .unwrap();
+
+        runtime
+            .block_on(future::lazy::<_, Result<(), ()>>(|| {
+                // Rows 0 through 4 are duplicates.
+                for i in 0..10 {
+                    writer.insert_ignore(row(i));
+                }
+                writer.upsert(row(3));
+                writer.update_ignore(row(20));
+                writer.delete_ignore(row(21));
+                writer.delete(row(22));
+                Ok(())
+            })).unwrap();
+        let stats = runtime
+            .block_on(future::poll_fn(|| writer.poll_flush()))
+            .unwrap();
+        assert_eq!(14, stats.operations());
+        assert_eq!(7, stats.ignored_row_errors());
+        assert_eq!(1, stats.failed_operations());
+        assert_eq!(13, stats.successful_operations());
+
+        let errors = writer.take_errors();
+        assert_eq!(1, errors.len());
+        assert_eq!(OperationKind::Delete, errors.errors()[0].kind);
+        assert_eq!(
+            StatusCode::NotFound,
+            errors.errors()[0].status().unwrap().code()
+        );
+    }
+
+    #[test]
+    fn flush_interval() {
+        let _ = env_logger::try_init();
+        let mut cluster = MiniCluster::default();
+        let mut runtime = Runtime::new().unwrap();
+
+        let mut client = runtime
+            .block_on(Client::new(cluster.master_addrs(), Options::default()))
+            .expect("client");
+
+        let schema = SchemaBuilder::new()
+            .add_column(Column::new("key", DataType::Int32).set_not_null())
+            .set_primary_key(vec!["key"])
+            .build()
+            .unwrap();
+
+        let mut table_builder = TableBuilder::new("flush_interval", schema.clone());
+        table_builder.set_range_partition_columns(vec!["key"]);
+        table_builder.set_num_replicas(1);
+
+        let table = create_table(&mut runtime, &mut client, table_builder);
+
+        let flush_interval = Duration::from_millis(100);
+        let mut config = WriterConfig::default();
+        config.flush_interval(Some(flush_interval));
+        let mut writer = table.new_writer(config);
+
+        // Apply operations as they trickle in from a channel, polling the writer only when the
+        // task is notified.
+        let (send, mut recv) = mpsc::unbounded::<Row<'static>>();
+        runtime.spawn(future::poll_fn(move || loop {
+            if writer.poll_ready().expect("write failed").is_not_ready() {
+                return Ok(Async::NotReady);
+            }
+            match recv.poll()? {
+                Async::Ready(Some(row)) => writer.insert(row),
+                Async::Ready(None) => return Ok(Async::Ready(())),
+                Async::NotReady => return Ok(Async::NotReady),
+            }
+        }));
+
+        let mut insert = table.schema().new_row();
+        insert.set("key", 0i32).unwrap();
+        send.unbounded_send(insert).unwrap();
+
+        // The row is written once the flush interval elapses, without flushing the writer.
+        let num_rows = runtime
+            .block_on(Delay::new(Instant::now() + flush_interval * 10).then(|result| {
+                result.expect("timer failed");
+                table
+                    .scan_builder()
+                    .build()
+                    .fold(0, |num_rows, batch| Ok::<_, Error>(num_rows + batch.num_rows()))
+            })).unwrap();
+        assert_eq!(1, num_rows);
+    }
+
+    #[test]
+    fn reroute() {
+        let _ = env_logger::try_init();
+        let mut cluster = MiniCluster::default();
+        let mut runtime = Runtime::new().unwrap();
+
+        let mut client = runtime
+            .block_on(Client::new(cluster.master_addrs(), Options::default()))
+            .expect("client");
+
+        let schema = SchemaBuilder::new()
+            .add_column(Column::new("key", DataType::Int32).set_not_null())
+            .set_primary_key(vec!["key"])
+            .build()
+            .unwrap();
+
+        let mut table_builder = TableBuilder::new("reroute", schema.clone());
+        table_builder.set_range_partition_columns(vec!["key"]);
+        table_builder.set_num_replicas(1);
+
+        let table = create_table(&mut runtime, &mut client, table_builder);
+        let mut writer = table.new_writer(WriterConfig::default());
+
+        for round in 0..2 {
+            // TODO: remove lazy once apply no longer polls.
+            runtime
+                .block_on(future::lazy::<_, Result<(), ()>>(|| {
+                    for i in 0..10i32 {
+                        let mut insert = table.schema().new_row();
+                        insert.set("key", i).unwrap();
+                        writer.insert(insert);
+                    }
+                    Ok(())
+                })).unwrap();
+
+            let stats = runtime
+                .block_on(future::poll_fn(|| writer.poll_flush()))
+                .unwrap();
+            assert_eq!(10, stats.successful_operations());
+            assert_eq!(0, stats.failed_batches());
+            assert!(writer.take_errors().is_empty());
+
+            if round == 0 {
+                // Replace the tablet, so that the writer's cached tablet location is stale.
+                let mut alter_builder = AlterTableBuilder::new();
+                alter_builder
+                    .drop_range_partition(
+                        &RangePartitionBound::Inclusive(schema.new_row()),
+                        &RangePartitionBound::Exclusive(schema.new_row()),
+                    ).add_range_partition(
+                        &RangePartitionBound::Inclusive(schema.new_row()),
+                        &RangePartitionBound::Exclusive(schema.new_row()),
+                    );
+                runtime
+                    .block_on(client.alter_table_by_id(table.id(), alter_builder))
+                    .unwrap();
+            }
+        }
+
+        // Drop the tablet without replacing it, so that the re-routed operations fail.
+        let mut alter_builder = AlterTableBuilder::new();
+        alter_builder.drop_range_partition(
+            &RangePartitionBound::Inclusive(schema.new_row()),
+            &RangePartitionBound::Exclusive(schema.new_row()),
+        );
+        runtime
+            .block_on(client.alter_table_by_id(table.id(), alter_builder))
+            .unwrap();
+
+        runtime
+            .block_on(future::lazy::<_, Result<(), ()>>(|| {
+                for i in 0..10i32 {
+                    let mut insert = table.schema().new_row();
+                    insert.set("key", i).unwrap();
+                    writer.insert(insert);
+                }
+                Ok(())
+            })).unwrap();
+        runtime
+            .block_on(future::poll_fn(|| writer.poll_flush()))
+            .unwrap();
+        assert_eq!(0, writer.buffered_data);
+
+        let errors = writer.take_errors();
+        assert_eq!(10, errors.len());
+        for error in errors {
+            match error.error {
+                Error::NoRangePartition => (),
+                ref error => panic!("unexpected error: {:?}", error),
+            }
+        }
+    }
+
+    #[test]
+    fn leader_failure() {
+        let _ = env_logger::try_init();
+        let mut cluster = MiniCluster::new(MiniClusterConfig::default().num_tservers(3));
+        let mut runtime = Runtime::new().unwrap();
+
+        let mut client = runtime
+            .block_on(Client::new(cluster.master_addrs(), Options::default()))
+            .expect("client");
+
+        let schema = SchemaBuilder::new()
+            .add_column(Column::new("key", DataType::Int32).set_not_null())
+            .set_primary_key(vec!["key"])
+            .build()
+            .unwrap();
+
+        let mut table_builder = TableBuilder::new("leader_failure", schema.clone());
+        table_builder.set_range_partition_columns(vec!["key"]);
+        table_builder.set_num_replicas(3);
+
+        let table = create_table(&mut runtime, &mut client, table_builder);
+        let num_rows = 10_000i32;
+
+        // Write half of the rows, so that the tablet has an elected leader.
+        insert_rows(&mut runtime, &table, num_rows / 2, |row, i| {
+            row.set("key", i).unwrap();
+        });
+
+        let tablets = runtime.block_on(table.tablets().collect()).unwrap();
+        assert_eq!(1, tablets.len());
+        let leader = tablets[0]
+            .replicas()
+            .iter()
+            .find(|replica| replica.role() == RaftRole::Leader)
+            .expect("leader replica")
+            .rpc_addrs()[0]
+            .clone();
+        let leader_index = cluster
+            .tserver_addrs()
+            .iter()
+            .position(|addr| *addr == leader)
+            .expect("leader tablet server");
+
+        let mut writer = table.new_writer(WriterConfig::default());
+
+        // Buffer the remaining rows, and stop the leader before they are flushed. The batches
+        // must be retried on the newly elected leader.
+        runtime
+            .block_on(future::lazy::<_, Result<(), ()>>(|| {
+                for i in num_rows / 2..num_rows {
+                    let mut insert = table.schema().new_row();
+                    insert.set("key", i).unwrap();
+                    writer.insert(insert);
+                }
+                Ok(())
+            })).unwrap();
+        cluster.stop_tserver(leader_index as u32);
+
+        let stats = runtime
+            .block_on(future::poll_fn(|| writer.poll_flush()))
+            .unwrap();
+        assert_eq!(num_rows as usize / 2, stats.successful_operations());
+        assert_eq!(0, stats.failed_batches());
+        assert!(writer.take_errors().is_empty());
+
+        let num_scanned = runtime
+            .block_on(
+                table
+                    .scan_builder()
+                    .build()
+                    .fold(0, |num_rows, batch| Ok::<_, Error>(num_rows + batch.num_rows())),
+            ).unwrap();
+        assert_eq!(num_rows as usize, num_scanned);
     }
 }